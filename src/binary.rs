@@ -0,0 +1,455 @@
+//! A compact binary format for tiling graphs.
+//!
+//! All integers are stored as LEB128 varints.  The format consists of:
+//!
+//! * the magic bytes `DCCG`, followed by a version byte,
+//! * the width and height of the boards, their topology (as an index into `Topology::all`),
+//!   and the number of nodes,
+//! * each board in turn, as a bit-packed mask of its marked positions (in row-major order,
+//!   least significant bit first), padded to a whole number of bytes,
+//! * the number of complete nodes, followed by their (sorted, delta-encoded) indices,
+//! * an offset table: for each node, and then once more at the end, the position of its edges
+//!   in the next section (as a little-endian `u64`, counting from the start of the section),
+//! * for each node, its out-degree followed by the (sorted, delta-encoded) indices of its children.
+//!
+//! Reverse edges are not stored, since they can be recovered from the edges.  Since every board
+//! takes up the same number of bytes, and the offset table tells us where the edges of each node
+//! start, a `MappedGraph` can read single nodes and their edges without decoding the rest of the
+//! graph.
+
+use crate::board::RectangularBoard;
+use crate::graph::BoardGraph;
+use crate::topology::Topology;
+use memmap2::Mmap;
+use std::convert::TryFrom;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::path::Path;
+
+/// The bytes at the start of every graph in our format
+pub const MAGIC: &[u8; 4] = b"DCCG";
+
+const VERSION: u8 = 1;
+
+/// The number of bytes in each entry of the offset table
+const OFFSET_LEN: usize = 8;
+
+fn write_varint<W: Write>(w: &mut W, mut value: usize) -> Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            return w.write_all(&[byte]);
+        }
+
+        w.write_all(&[byte | 0x80])?;
+    }
+}
+
+/// Writes a list of indices as the length of the list, then the sorted indices as
+/// differences from the previous one
+fn write_delta_list<W: Write>(w: &mut W, mut indices: Vec<usize>) -> Result<()> {
+    indices.sort_unstable();
+    write_varint(w, indices.len())?;
+
+    let mut previous = 0;
+
+    for i in indices {
+        write_varint(w, i - previous)?;
+        previous = i;
+    }
+
+    Ok(())
+}
+
+/// Writes a graph in our binary format.  All boards in the graph should have the same dimensions.
+pub fn write_graph<W: Write>(graph: &BoardGraph, w: &mut W) -> Result<()> {
+    let (width, height, topology) = graph.get_node(0).map_or((0, 0, Topology::Plane), |board| {
+        (board.width, board.height, board.topology)
+    });
+
+    w.write_all(MAGIC)?;
+    w.write_all(&[VERSION])?;
+    write_varint(w, width)?;
+    write_varint(w, height)?;
+    write_varint(w, topology.index())?;
+    write_varint(w, graph.len())?;
+
+    let mut mask = vec![0u8; (width * height).div_ceil(8)];
+
+    for i in 0..graph.len() {
+        let board = graph.get_node(i).unwrap();
+        mask.iter_mut().for_each(|byte| *byte = 0);
+
+        for (bit, marked) in board.board.iter().flatten().enumerate() {
+            if *marked {
+                mask[bit / 8] |= 1 << (bit % 8);
+            }
+        }
+
+        w.write_all(&mask)?;
+    }
+
+    write_delta_list(
+        w,
+        (0..graph.len()).filter(|i| graph.is_complete(*i)).collect(),
+    )?;
+
+    // we need to know where the edges of each node start before we can write them
+    let mut edges = Vec::new();
+    let mut offsets = Vec::with_capacity(graph.len() + 1);
+
+    for i in 0..graph.len() {
+        offsets.push(edges.len() as u64);
+        write_delta_list(
+            &mut edges,
+            graph.get_edges(i).into_iter().flatten().copied().collect(),
+        )?;
+    }
+    offsets.push(edges.len() as u64);
+
+    for offset in offsets {
+        w.write_all(&offset.to_le_bytes())?;
+    }
+    w.write_all(&edges)
+}
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Reads values from a byte slice
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + len)
+            .ok_or_else(|| invalid_data("unexpected end of graph"))?;
+
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn read_varint(&mut self) -> Result<usize> {
+        let mut value = 0;
+
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.read_bytes(1)?[0];
+            value |= ((byte & 0x7f) as usize) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(invalid_data("varint is too long"))
+    }
+
+    /// Reads a list of indices written by `write_delta_list`, checking that they're less than `bound`
+    fn read_delta_list(&mut self, bound: usize) -> Result<Vec<usize>> {
+        let len = self.read_varint()?;
+        let mut indices = Vec::with_capacity(len.min(bound));
+        let mut previous = 0;
+
+        for _ in 0..len {
+            previous += self.read_varint()?;
+
+            if previous >= bound {
+                return Err(invalid_data("node index out of range"));
+            }
+            indices.push(previous);
+        }
+
+        Ok(indices)
+    }
+}
+
+/// The dimensions of the boards and the number of nodes of a graph, which come at the start
+/// of our format
+struct Header {
+    width: usize,
+    height: usize,
+    topology: Topology,
+    len: usize,
+
+    // The number of bytes taken up by each board
+    mask_len: usize,
+}
+
+impl Reader<'_> {
+    fn read_header(&mut self) -> Result<Header> {
+        if self.read_bytes(MAGIC.len())? != MAGIC {
+            return Err(invalid_data("not a tiling graph"));
+        }
+        if self.read_bytes(1)?[0] != VERSION {
+            return Err(invalid_data("unsupported tiling graph version"));
+        }
+
+        let width = self.read_varint()?;
+        let height = self.read_varint()?;
+        let topology = *Topology::all()
+            .get(self.read_varint()?)
+            .ok_or_else(|| invalid_data("unknown topology"))?;
+        let len = self.read_varint()?;
+        let mask_len = width
+            .checked_mul(height)
+            .ok_or_else(|| invalid_data("board is too large"))?
+            .div_ceil(8);
+
+        if len > 0 && mask_len == 0 {
+            return Err(invalid_data("board is empty"));
+        }
+
+        Ok(Header {
+            width,
+            height,
+            topology,
+            len,
+            mask_len,
+        })
+    }
+
+    /// Reads a table of `count` offsets, returning the position of its start
+    fn skip_offsets(&mut self, count: usize) -> Result<usize> {
+        let start = self.offset;
+        let len = count
+            .checked_mul(OFFSET_LEN)
+            .ok_or_else(|| invalid_data("graph is too large"))?;
+
+        self.read_bytes(len)?;
+        Ok(start)
+    }
+}
+
+impl Header {
+    /// Decodes a board from its bit-packed mask
+    fn board(&self, mask: &[u8]) -> RectangularBoard {
+        let board = (0..self.height)
+            .map(|i| {
+                (0..self.width)
+                    .map(|j| {
+                        let bit = i * self.width + j;
+                        mask[bit / 8] & (1 << (bit % 8)) != 0
+                    })
+                    .collect()
+            })
+            .collect();
+
+        RectangularBoard::from_marks(board).with_topology(self.topology)
+    }
+}
+
+/// Reads a graph in our binary format from a byte slice, decoding the whole graph
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::binary::{read_graph, write_graph};
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::graph::BoardGraph;
+///
+/// let board = RectangularBoard::new(2, 1);
+/// let mut graph = BoardGraph::new();
+/// graph.add_node(board.clone());
+/// graph.add_node(board.place_tile_at(&[(0, 0), (0, 1)]).unwrap());
+/// graph.add_edge(0, 1);
+/// graph.mark_node_as_complete(1);
+///
+/// let mut bytes = Vec::new();
+/// write_graph(&graph, &mut bytes).unwrap();
+/// let loaded = read_graph(&bytes).unwrap();
+///
+/// assert_eq!(loaded.find_node(&board), Some(0));
+/// assert!(loaded.is_complete(1));
+/// assert!(read_graph(&bytes[..bytes.len() - 1]).is_err());
+/// ```
+pub fn read_graph(bytes: &[u8]) -> Result<BoardGraph> {
+    let mut reader = Reader { bytes, offset: 0 };
+    let header = reader.read_header()?;
+    let len = header.len;
+
+    let mut graph = BoardGraph::new();
+
+    for _ in 0..len {
+        let board = header.board(reader.read_bytes(header.mask_len)?);
+
+        if graph.add_node(board) != graph.len() - 1 {
+            return Err(invalid_data("duplicate board"));
+        }
+    }
+
+    for i in reader.read_delta_list(len)? {
+        graph.mark_node_as_complete(i);
+    }
+
+    // we read the edges in order, so we don't need the offsets
+    reader.skip_offsets(len + 1)?;
+
+    for s in 0..len {
+        for t in reader.read_delta_list(len)? {
+            graph.add_edge(s, t);
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Reads a graph in our binary format from a file, decoding the whole graph.  Use `MappedGraph`
+/// to read the nodes and edges of a large graph only as they're needed.
+pub fn open_graph<P: AsRef<Path>>(path: P) -> Result<BoardGraph> {
+    read_graph(&std::fs::read(path)?)
+}
+
+/// A graph in our binary format which hasn't been decoded, such as a memory-mapped file.
+/// Nodes and their edges are decoded each time they're asked for, so only the parts of the
+/// graph we look at are read.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::binary::{write_graph, MappedGraph};
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::graph::BoardGraph;
+///
+/// let board = RectangularBoard::new(2, 2);
+/// let mut graph = BoardGraph::new();
+/// graph.add_node(board.clone());
+/// graph.add_node(board.place_tile_at(&[(0, 0), (0, 1)]).unwrap());
+/// graph.add_node(board.place_tile_at(&[(0, 0), (1, 0)]).unwrap());
+/// graph.add_edge(0, 1);
+/// graph.add_edge(0, 2);
+///
+/// let mut bytes = Vec::new();
+/// write_graph(&graph, &mut bytes).unwrap();
+///
+/// let mapped = MappedGraph::new(bytes).unwrap();
+/// assert_eq!(mapped.len(), 3);
+/// assert_eq!(mapped.get_node(0), Some(board));
+/// assert_eq!(mapped.get_edges(0).unwrap(), vec![1, 2]);
+/// assert!(mapped.get_edges(1).unwrap().is_empty());
+/// assert!(!mapped.is_complete(2));
+/// ```
+pub struct MappedGraph<B: AsRef<[u8]> = Mmap> {
+    bytes: B,
+    header: Header,
+
+    // The sorted indices of the complete nodes
+    complete: Vec<usize>,
+
+    // The positions of the boards, the offset table and the edges in `bytes`
+    boards_start: usize,
+    offsets_start: usize,
+    edges_start: usize,
+}
+
+impl MappedGraph<Mmap> {
+    /// Memory-maps a file containing a graph in our binary format
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+
+        // Safety: the file must not be modified while we're reading it
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        MappedGraph::new(mmap)
+    }
+}
+
+impl<B: AsRef<[u8]>> MappedGraph<B> {
+    /// Reads the header of a graph in our binary format, without decoding any nodes or edges
+    pub fn new(bytes: B) -> Result<Self> {
+        let mut reader = Reader {
+            bytes: bytes.as_ref(),
+            offset: 0,
+        };
+        let header = reader.read_header()?;
+
+        let boards_start = reader.offset;
+        let boards_len = header
+            .len
+            .checked_mul(header.mask_len)
+            .ok_or_else(|| invalid_data("graph is too large"))?;
+        reader.read_bytes(boards_len)?;
+
+        let complete = reader.read_delta_list(header.len)?;
+        let offsets_start = reader.skip_offsets(header.len + 1)?;
+        let edges_start = reader.offset;
+
+        Ok(MappedGraph {
+            bytes,
+            header,
+            complete,
+            boards_start,
+            offsets_start,
+            edges_start,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.header.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.header.len == 0
+    }
+
+    pub fn is_complete(&self, i: usize) -> bool {
+        self.complete.binary_search(&i).is_ok()
+    }
+
+    /// Decodes the board at the given node, or returns None if there's no such node
+    pub fn get_node(&self, i: usize) -> Option<RectangularBoard> {
+        if i >= self.len() {
+            return None;
+        }
+
+        let start = self.boards_start + i * self.header.mask_len;
+        let mask = &self.bytes.as_ref()[start..start + self.header.mask_len];
+
+        Some(self.header.board(mask))
+    }
+
+    /// Returns the `i`-th entry of the offset table
+    fn offset(&self, i: usize) -> Result<usize> {
+        let start = self.offsets_start + i * OFFSET_LEN;
+        let mut offset = [0; OFFSET_LEN];
+        offset.copy_from_slice(&self.bytes.as_ref()[start..start + OFFSET_LEN]);
+
+        usize::try_from(u64::from_le_bytes(offset)).map_err(|_| invalid_data("offset is too large"))
+    }
+
+    /// Decodes the children of the given node, in increasing order
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there's no such node.
+    pub fn get_edges(&self, i: usize) -> Result<Vec<usize>> {
+        assert!(i < self.len());
+
+        let (start, end) = (self.offset(i)?, self.offset(i + 1)?);
+        let bytes = self
+            .bytes
+            .as_ref()
+            .get(self.edges_start..)
+            .and_then(|edges| edges.get(start..end))
+            .ok_or_else(|| invalid_data("edges out of range"))?;
+
+        let mut reader = Reader { bytes, offset: 0 };
+        let edges = reader.read_delta_list(self.len())?;
+
+        if reader.offset != bytes.len() {
+            return Err(invalid_data("edges don't match the offset table"));
+        }
+
+        Ok(edges)
+    }
+
+    /// Decodes the whole graph
+    pub fn to_graph(&self) -> Result<BoardGraph> {
+        read_graph(self.bytes.as_ref())
+    }
+}
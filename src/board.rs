@@ -1,42 +1,146 @@
-use crate::tile::{Direction, Tile, TileCollection};
-use serde_derive::Serialize;
+use crate::placement::Placement;
+use crate::selector::SearchOptions;
+use crate::symmetry::Symmetry;
+use crate::tile::{Axis, Direction, Tile, TileCollection};
+use crate::topology::Topology;
+use serde_derive::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::fmt;
 
-#[derive(Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "SerializedBoard")]
 pub struct RectangularBoard {
-    #[serde(skip_serializing)]
     pub width: usize,
 
-    #[serde(skip_serializing)]
     pub height: usize,
 
     pub board: Vec<Vec<bool>>,
 
+    #[serde(skip_serializing_if = "Topology::is_plane")]
+    pub topology: Topology,
+
+    // The number of sides of each position which are either on the edge of the board, or
+    // next to a marked position.  This is determined by `board`, but we keep track of it
+    // as we go so that we don't need to recompute it every time we place down a tile.
     #[serde(skip_serializing)]
     counts: Vec<Vec<usize>>,
+
+    // The marked positions as a bitmask, where `(row, col)` is bit `row * width + col`.
+    // Like `counts`, this is determined by `board`.
+    #[serde(skip_serializing)]
+    mask: Vec<u64>,
+}
+
+/// The data needed to reconstruct a `RectangularBoard`
+#[derive(Deserialize)]
+struct SerializedBoard {
+    width: usize,
+    height: usize,
+    board: Vec<Vec<bool>>,
+    #[serde(default)]
+    topology: Topology,
+}
+
+impl TryFrom<SerializedBoard> for RectangularBoard {
+    type Error = String;
+
+    fn try_from(board: SerializedBoard) -> Result<Self, Self::Error> {
+        if board.board.len() != board.height || board.board.iter().any(|r| r.len() != board.width) {
+            return Err(format!(
+                "board doesn't have dimensions {}x{}",
+                board.width, board.height
+            ));
+        }
+
+        Ok(RectangularBoard::from_marks(board.board).with_topology(board.topology))
+    }
 }
 
 impl RectangularBoard {
     pub fn new(width: usize, height: usize) -> Self {
-        let mut counts = vec![vec![0; width]; height];
+        RectangularBoard::from_marks(vec![vec![false; width]; height])
+    }
 
-        for row in counts.iter_mut() {
-            row[0] = 1;
-            row[width - 1] = 1;
-        }
+    /// Creates a board from its rows, where `true` indicates a marked position.  The rows should
+    /// all have the same length.  There may be no rows, or rows with no positions, in which
+    /// case the board is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::from_marks(vec![vec![true, false]]);
+    /// assert_eq!(board, RectangularBoard::new(2, 1).place_tile_at(&[(0, 0)]).unwrap());
+    ///
+    /// assert!(RectangularBoard::from_marks(Vec::new()).is_all_marked());
+    /// ```
+    pub fn from_marks(board: Vec<Vec<bool>>) -> Self {
+        RectangularBoard::from_marks_with_topology(board, Topology::Plane)
+    }
 
-        for j in 0..width {
-            counts[0][j] = 1;
-            counts[height - 1][j] = 1;
-        }
+    /// Returns a copy of this board with its edges glued together according to the given topology
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::selector::SearchOptions;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::topology::Topology;
+    ///
+    /// // a domino can wrap around a cylinder of circumference 3
+    /// let board = RectangularBoard::new(3, 1).with_topology(Topology::Cylinder);
+    /// assert!(board.place_tile_at(&[(0, 0)]).unwrap().place_tile_at(&[(0, 2)]).is_some());
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    /// let board = board.place_tile_at(&[(0, 1)]).unwrap();
+    /// assert_eq!(board.place_tile(&tiles, &SearchOptions::default()).len(), 1);
+    ///
+    /// // a board with rows but no columns has nothing to glue together
+    /// let board = RectangularBoard::from_marks(vec![Vec::new(); 3]).with_topology(Topology::Torus);
+    /// assert!(board.place_tile(&tiles, &SearchOptions::default()).is_empty());
+    /// ```
+    pub fn with_topology(&self, topology: Topology) -> Self {
+        RectangularBoard::from_marks_with_topology(self.board.clone(), topology)
+    }
 
-        RectangularBoard {
+    fn from_marks_with_topology(board: Vec<Vec<bool>>, topology: Topology) -> Self {
+        let height = board.len();
+        let width = board.first().map_or(0, |row| row.len());
+
+        let mut new_board = RectangularBoard {
             width,
             height,
             board: vec![vec![false; width]; height],
-            counts,
+            topology,
+            counts: vec![vec![0; width]; height],
+            mask: vec![0; (width * height).div_ceil(64)],
+        };
+
+        // positions on an edge of the board (which isn't glued to another edge) start with a count of 1
+        for i in 0..height {
+            for j in 0..width {
+                let on_edge = NEIGHBOURS.iter().any(|(dx, dy)| {
+                    new_board
+                        .wrap(Position::new(i as isize + dx, j as isize + dy))
+                        .is_none()
+                });
+
+                new_board.counts[i][j] = on_edge as usize;
+            }
+        }
+
+        for (i, row) in board.iter().enumerate() {
+            for (j, marked) in row.iter().enumerate() {
+                if *marked {
+                    new_board.mark(Position::from((i, j)));
+                }
+            }
         }
+
+        new_board
     }
 
     /// Generates a new L-tetromino shaped board.
@@ -45,15 +149,13 @@ impl RectangularBoard {
     /// with long side having length n, and then we replace each
     /// box with a scale^2 box.
     pub fn l_board(n: usize, scale: usize) -> Self {
-        let mut board = RectangularBoard::new(n * scale, 2 * scale);
+        let mut board = vec![vec![false; n]; 2];
 
-        for row in 0..scale {
-            for col in scale..(n * scale) {
-                board.board[row][col] = true;
-            }
+        for marked in board[0].iter_mut().skip(1) {
+            *marked = true;
         }
 
-        board
+        RectangularBoard::from_marks(board).scale(scale)
     }
 
     /// Generates a new T-tetromino shaped board.
@@ -62,18 +164,347 @@ impl RectangularBoard {
     /// where the two tils have length n, and then we replace
     /// each box with a scale^2 box.
     pub fn t_board(n: usize, scale: usize) -> Self {
-        let mut board = RectangularBoard::new((2 * n + 1) * scale, 2 * scale);
+        let mut board = vec![vec![false; 2 * n + 1]; 2];
 
-        for row in 0..scale {
-            for col in 0..(n * scale) {
-                board.board[row][col] = true;
-            }
-            for col in ((n + 1) * scale)..((2 * n + 1) * scale) {
-                board.board[row][col] = true;
+        for (col, marked) in board[0].iter_mut().enumerate() {
+            // leave the middle section of the top row unmarked
+            *marked = col != n;
+        }
+
+        RectangularBoard::from_marks(board).scale(scale)
+    }
+
+    /// Generates the Aztec diamond of order n: the positions of a 2n x 2n board lying within a
+    /// diamond, so that the rows have length 2, 4, ..., 2n, 2n, ..., 4, 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::aztec_diamond(2);
+    /// assert_eq!(board.board[0], vec![true, false, false, true]);
+    /// assert_eq!(board.board[1], vec![false, false, false, false]);
+    /// ```
+    pub fn aztec_diamond(n: usize) -> Self {
+        RectangularBoard::from_marks(
+            (0..2 * n)
+                .map(|row| {
+                    // the number of unmarked positions on either side of the middle of this row
+                    let half = row.min(2 * n - 1 - row) + 1;
+                    (0..2 * n)
+                        .map(|col| col + half < n || col >= n + half)
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Generates the Young diagram of the given partition: its rows have the lengths given by
+    /// `partition`, from top to bottom, and are lined up on the left
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the row lengths increase from one row to the next.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::young_diagram(&[3, 1]);
+    /// assert_eq!(board.board, vec![vec![false, false, false], vec![false, true, true]]);
+    /// ```
+    pub fn young_diagram(partition: &[usize]) -> Self {
+        assert!(
+            partition.windows(2).all(|pair| pair[0] >= pair[1]),
+            "{:?} isn't a partition",
+            partition
+        );
+
+        let width = partition.iter().copied().max().unwrap_or(0);
+
+        RectangularBoard::from_marks(
+            partition
+                .iter()
+                .map(|length| (0..width).map(|col| col >= *length).collect())
+                .collect(),
+        )
+    }
+
+    /// Generates the staircase with n steps, the Young diagram of the partition (n, n - 1, ..., 1)
+    pub fn staircase(n: usize) -> Self {
+        RectangularBoard::young_diagram(&(1..=n).rev().collect::<Vec<_>>())
+    }
+
+    /// Generates a square with `side` boxes along each side, with a `hole` x `hole` square
+    /// removed from its centre (rounding up and to the left if it can't be centred exactly)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::holey_square(4, 2);
+    /// assert_eq!(board.board[1], vec![false, true, true, false]);
+    /// ```
+    pub fn holey_square(side: usize, hole: usize) -> Self {
+        let offset = side.saturating_sub(hole) / 2;
+
+        RectangularBoard::new(side, side)
+            .difference(&RectangularBoard::new(hole, hole).translate(offset, offset))
+    }
+
+    /// Generates a cross (or plus sign), made up of a `thickness` x `thickness` square with
+    /// an arm of length `arm` and width `thickness` attached to each side
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// // the X pentomino
+    /// let board = RectangularBoard::cross(1, 1);
+    /// assert_eq!(board, RectangularBoard::from_cells(&[(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]));
+    /// ```
+    pub fn cross(arm: usize, thickness: usize) -> Self {
+        let length = 2 * arm + thickness;
+
+        RectangularBoard::new(length, thickness)
+            .translate(arm, 0)
+            .union(&RectangularBoard::new(thickness, length).translate(0, arm))
+    }
+
+    /// Generates the mutilated chessboard: an n x n square with its top left and
+    /// bottom right corners removed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::mutilated_chessboard(8);
+    /// assert!(board.board[0][0] && board.board[7][7]);
+    /// assert_eq!(board.board.iter().flatten().filter(|marked| !**marked).count(), 62);
+    /// ```
+    pub fn mutilated_chessboard(n: usize) -> Self {
+        let mut board = vec![vec![false; n]; n];
+
+        if n > 0 {
+            board[0][0] = true;
+            board[n - 1][n - 1] = true;
+        }
+
+        RectangularBoard::from_marks(board)
+    }
+
+    /// Creates the smallest board containing the given `(row, col)` cells, with every
+    /// other position marked
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a cell has a negative row or column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// // the X pentomino
+    /// let board = RectangularBoard::from_cells(&[(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]);
+    /// assert_eq!((board.width, board.height), (3, 3));
+    /// assert!(board.board[0][0] && !board.board[0][1]);
+    /// ```
+    pub fn from_cells(cells: &[(isize, isize)]) -> Self {
+        assert!(cells.iter().all(|(row, col)| *row >= 0 && *col >= 0));
+
+        let height = cells.iter().map(|c| c.0 as usize + 1).max().unwrap_or(0);
+        let width = cells.iter().map(|c| c.1 as usize + 1).max().unwrap_or(0);
+
+        let mut board = vec![vec![true; width]; height];
+
+        for (row, col) in cells {
+            board[*row as usize][*col as usize] = false;
+        }
+
+        RectangularBoard::from_marks(board)
+    }
+
+    /// Creates a board in the shape of the given tile, with each of its boxes
+    /// replaced by a scale^2 box
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::Tile;
+    ///
+    /// let board = RectangularBoard::from_tile(&Tile::l_tile(2), 2);
+    /// assert_eq!((board.width, board.height), (4, 4));
+    /// assert_eq!(board.board.iter().flatten().filter(|marked| !**marked).count(), 12);
+    /// ```
+    pub fn from_tile(tile: &Tile, scale: usize) -> Self {
+        RectangularBoard::from_cells(&tile.cells()).scale(scale)
+    }
+
+    /// Returns a copy of this board with each position replaced by a scale^2 box of positions,
+    /// each marked if the original position was marked
+    ///
+    /// The operations on boards which change their shape (`scale`, `translate`, `rotate`,
+    /// `reflect`, `union` and `difference`) only look at the marked positions, and return
+    /// boards whose edges aren't glued together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// assert_eq!(RectangularBoard::l_board(3, 1).scale(2), RectangularBoard::l_board(3, 2));
+    /// ```
+    pub fn scale(&self, scale: usize) -> Self {
+        RectangularBoard::from_marks(
+            (0..self.height * scale)
+                .map(|row| {
+                    (0..self.width * scale)
+                        .map(|col| self.board[row / scale][col / scale])
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns a copy of this board moved down by `rows` and right by `cols`, adding
+    /// marked positions above and to the left of it
+    pub fn translate(&self, rows: usize, cols: usize) -> Self {
+        RectangularBoard::from_marks(
+            (0..self.height + rows)
+                .map(|row| {
+                    (0..self.width + cols)
+                        .map(|col| row < rows || col < cols || self.board[row - rows][col - cols])
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the image of this board under the given symmetry.  Unlike `Symmetry::apply`,
+    /// this works for boards which aren't square: the width and height are swapped as needed.
+    fn transform(&self, symmetry: Symmetry) -> Self {
+        let (width, height) = if symmetry.requires_square() {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+        let mut board = vec![vec![false; width]; height];
+
+        for (row, marks) in self.board.iter().enumerate() {
+            for (col, marked) in marks.iter().enumerate() {
+                let (r, c) = symmetry.apply((row, col), self.width, self.height);
+                board[r][c] = *marked;
             }
         }
 
-        board
+        RectangularBoard::from_marks(board)
+    }
+
+    /// Returns a rotated (by 90 degrees clockwise) copy of this board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::l_board(3, 1).rotate();
+    /// assert_eq!(board.board, vec![vec![false, false], vec![false, true], vec![false, true]]);
+    /// ```
+    pub fn rotate(&self) -> Self {
+        self.transform(Symmetry::Rotate90)
+    }
+
+    /// Returns a reflected (about the specified axis) copy of this board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::Axis;
+    ///
+    /// let board = RectangularBoard::l_board(3, 1).reflect(Axis::Vertical);
+    /// assert_eq!(board.board, vec![vec![true, true, false], vec![false, false, false]]);
+    /// ```
+    pub fn reflect(&self, axis: Axis) -> Self {
+        match axis {
+            Axis::Vertical => self.transform(Symmetry::ReflectVertical),
+            Axis::Horizontal => self.transform(Symmetry::ReflectHorizontal),
+        }
+    }
+
+    /// Returns whether the given position is an unmarked position of this board, where
+    /// positions beyond the edges of the board count as marked
+    fn is_unmarked(&self, (row, col): (usize, usize)) -> bool {
+        row < self.height && col < self.width && !self.board[row][col]
+    }
+
+    /// Combines this board with another board (lined up at their top left corners), giving
+    /// a `width` x `height` board in which a position is unmarked according to `keep`
+    fn combine<F: Fn(bool, bool) -> bool>(
+        &self,
+        other: &RectangularBoard,
+        width: usize,
+        height: usize,
+        keep: F,
+    ) -> Self {
+        RectangularBoard::from_marks(
+            (0..height)
+                .map(|row| {
+                    (0..width)
+                        .map(|col| {
+                            !keep(self.is_unmarked((row, col)), other.is_unmarked((row, col)))
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the board whose unmarked positions are those unmarked in either this board or
+    /// the other board, lining up their top left corners (use `translate` to move them apart)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// // a plus sign, made out of a horizontal and a vertical bar
+    /// let horizontal = RectangularBoard::new(3, 1).translate(1, 0);
+    /// let vertical = RectangularBoard::new(1, 3).translate(0, 1);
+    /// assert_eq!(
+    ///     horizontal.union(&vertical),
+    ///     RectangularBoard::from_cells(&[(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)])
+    /// );
+    /// ```
+    pub fn union(&self, other: &RectangularBoard) -> Self {
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+
+        self.combine(other, width, height, |a, b| a || b)
+    }
+
+    /// Returns the board whose unmarked positions are those unmarked in this board but not in
+    /// the other board, lining up their top left corners.  The result is the same size as
+    /// this board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// // a 3x3 square with a hole in the middle
+    /// let ring = RectangularBoard::new(3, 3).difference(&RectangularBoard::new(1, 1).translate(1, 1));
+    /// assert_eq!(ring.board[1], vec![false, true, false]);
+    /// ```
+    pub fn difference(&self, other: &RectangularBoard) -> Self {
+        self.combine(other, self.width, self.height, |a, b| a && !b)
     }
 
     /// What does it do?
@@ -90,24 +521,16 @@ impl RectangularBoard {
     /// // Example code here
     /// ```
     fn mark(&mut self, p: Position) {
-        for xp in (p.x - 1)..=(p.x + 1) {
-            if xp == p.x {
-                continue;
-            }
-            if self.is_valid(Position::from((xp, p.y))) {
-                self.counts[xp as usize][p.y as usize] += 1;
-            }
-        }
-        for yp in (p.y - 1)..=(p.y + 1) {
-            if yp == p.y {
-                continue;
-            }
-            if self.is_valid(Position::from((p.x, yp))) {
-                self.counts[p.x as usize][yp as usize] += 1;
+        for (dx, dy) in NEIGHBOURS.iter() {
+            if let Some(neighbour) = self.wrap(Position::new(p.x + dx, p.y + dy)) {
+                self.counts[neighbour.x as usize][neighbour.y as usize] += 1;
             }
         }
 
         self.board[p.x as usize][p.y as usize] = true;
+
+        let bit = p.x as usize * self.width + p.y as usize;
+        self.mask[bit / 64] |= 1 << (bit % 64);
     }
 
     /// Determines whether the entire board is marked
@@ -128,59 +551,418 @@ impl RectangularBoard {
         true
     }
 
-    pub fn place_tile(&self, tile_collection: &TileCollection) -> Vec<RectangularBoard> {
-        let mut largest_count = None;
-        let mut largest_position = None;
+    /// Returns the boards obtained by placing down a tile from the collection over the position
+    /// we would tile next, in every possible way
+    pub fn place_tile(
+        &self,
+        tile_collection: &TileCollection,
+        options: &SearchOptions,
+    ) -> Vec<RectangularBoard> {
+        self.place_tile_with_shapes(tile_collection, options)
+            .into_iter()
+            .map(|(_, board)| board)
+            .collect()
+    }
 
-        // find the position with the highest count
-        for j in 0..self.width {
-            for i in 0..self.height {
-                if !self.board[i][j] {
-                    let count = self.counts[i][j];
+    /// Like `place_tile`, but also returns the shape (as defined by the tile collection)
+    /// of the tile that was placed down to obtain each board
+    pub fn place_tile_with_shapes(
+        &self,
+        tile_collection: &TileCollection,
+        options: &SearchOptions,
+    ) -> Vec<(usize, RectangularBoard)> {
+        // For each fitting tile we find, return the corresponding board
+        self.map_fitting_placements(tile_collection, options, |placement| {
+            (placement.shape, self.with_placement(placement))
+        })
+    }
 
-                    // If our tile collection doesn't contain a 1x1 tile,
-                    // then we've found a spot that cannot be tiled, so we're done
-                    if !tile_collection.contains_single_tile() && count == 4 {
-                        return Vec::new();
-                    }
+    /// Places down a tile covering the given `(row, col)` positions.  Returns None
+    /// if any of the positions is not on the board, or is already marked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(2, 1);
+    /// let board = board.place_tile_at(&[(0, 0)]).unwrap();
+    ///
+    /// assert!(board.place_tile_at(&[(0, 0), (0, 1)]).is_none());
+    /// assert!(board.place_tile_at(&[(0, 1)]).unwrap().is_all_marked());
+    /// ```
+    pub fn place_tile_at(&self, positions: &[(usize, usize)]) -> Option<RectangularBoard> {
+        let covered: HashSet<Position> = positions.iter().map(|p| Position::from(*p)).collect();
 
-                    // keep track of the largest count we've found so far
-                    if largest_count.is_none() || self.counts[i][j] > largest_count.unwrap() {
-                        largest_count = Some(self.counts[i][j]);
-                        largest_position = Some((i, j));
-                    }
+        if covered
+            .iter()
+            .any(|p| !self.is_valid(*p) || self.is_marked(*p))
+        {
+            return None;
+        }
+
+        let mut child_board = self.clone();
+        for position in covered {
+            child_board.mark(position);
+        }
+        Some(child_board)
+    }
+
+    /// Returns the `(row, col)` positions which are marked in exactly one of the two boards
+    pub fn changed_positions(&self, other: &RectangularBoard) -> Vec<(usize, usize)> {
+        let mut positions = Vec::new();
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.board[i][j] ^ other.board[i][j] {
+                    positions.push((i, j));
+                }
+            }
+        }
+
+        positions
+    }
+
+    /// Returns the symmetries of the square which map this board to itself.
+    ///
+    /// The identity is always the first symmetry returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// assert_eq!(RectangularBoard::new(3, 3).symmetries().len(), 8);
+    /// assert_eq!(RectangularBoard::new(4, 3).symmetries().len(), 4);
+    /// assert_eq!(RectangularBoard::l_board(2, 1).symmetries().len(), 2);
+    /// ```
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        Symmetry::all()
+            .iter()
+            .copied()
+            .filter(|s| !s.requires_square() || self.width == self.height)
+            .filter(|s| self.topology.preserved_by(*s))
+            .filter(|s| {
+                (0..self.height).all(|i| {
+                    (0..self.width).all(|j| {
+                        let (si, sj) = s.apply((i, j), self.width, self.height);
+                        self.board[i][j] == self.board[si][sj]
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// Like `place_tile`, but keeps the board invariant under the given symmetry.
+    ///
+    /// Each tile is placed together with all of its images under the symmetry, so
+    /// repeatedly calling this function enumerates exactly those tilings which are
+    /// fixed by the symmetry.  The board should be invariant under the symmetry, and
+    /// the tile collection should be closed under it.
+    pub fn place_tile_symmetric(
+        &self,
+        tile_collection: &TileCollection,
+        options: &SearchOptions,
+        symmetry: Symmetry,
+    ) -> Vec<RectangularBoard> {
+        // Each orbit contains exactly one tile covering our chosen position, so distinct fitting
+        // tiles give distinct orbits.  Note that two orbits can still cover the same cells (and so
+        // result in equal boards), which is why we don't deduplicate the returned boards.
+        self.map_fitting_placements(tile_collection, options, |placement| {
+            self.symmetric_orbit(&placement.positions, symmetry)
+        })
+        .into_iter()
+        .flatten()
+        .map(|covered| {
+            let mut child_board = self.clone();
+            for position in covered {
+                child_board.mark(position);
+            }
+            child_board
+        })
+        .collect()
+    }
+
+    /// Computes the union of the images of the `(row, col)` positions covered by a tile under
+    /// repeated application of the given symmetry.  Returns None if two of the images overlap
+    /// without being equal, or if one of them covers a marked cell.
+    fn symmetric_orbit(
+        &self,
+        positions: &[(usize, usize)],
+        symmetry: Symmetry,
+    ) -> Option<HashSet<Position>> {
+        let mut images: Vec<HashSet<Position>> =
+            vec![positions.iter().map(|p| Position::from(*p)).collect()];
+
+        loop {
+            let image: HashSet<Position> = images
+                .last()
+                .unwrap()
+                .iter()
+                .map(|p| {
+                    let cell =
+                        symmetry.apply((p.x as usize, p.y as usize), self.width, self.height);
+                    Position::from(cell)
+                })
+                .collect();
+
+            if image == images[0] {
+                break;
+            }
+            images.push(image);
+        }
+
+        let mut covered = HashSet::new();
+
+        for image in images.iter() {
+            if image.iter().any(|p| self.is_marked(*p)) {
+                return None;
+            }
+
+            // images of a tile which aren't equal to one another must be disjoint
+            if !images
+                .iter()
+                .all(|other| other == image || other.is_disjoint(image))
+            {
+                return None;
+            }
+
+            covered.extend(image.iter().copied());
+        }
+
+        Some(covered)
+    }
+
+    /// Like `place_tile`, but also allows the position we're tiling to be left empty.
+    /// This is used to search for packings of the board, rather than tilings.
+    ///
+    /// Returns the boards obtained by placing down a tile over our chosen position, the
+    /// `(row, col)` of that position, and the board where the position has been marked without
+    /// placing down a tile (i.e. left as a hole).  Returns None if the board is completely marked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::selector::SearchOptions;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let board = RectangularBoard::new(1, 1);
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    ///
+    /// let options = SearchOptions::default();
+    ///
+    /// let (boards, hole, hole_board) = board.place_tile_or_hole(&tiles, &options).unwrap();
+    /// assert!(boards.is_empty());
+    /// assert_eq!(hole, (0, 0));
+    /// assert!(hole_board.is_all_marked());
+    /// ```
+    pub fn place_tile_or_hole(
+        &self,
+        tile_collection: &TileCollection,
+        options: &SearchOptions,
+    ) -> Option<(Vec<RectangularBoard>, (usize, usize), RectangularBoard)> {
+        let position = options.cell_selector.select(self, tile_collection)?;
+        let table = tile_collection.placement_table(self.width, self.height, self.topology);
+
+        let boards = table
+            .covering(position)
+            .filter(|placement| placement.fits(self))
+            .map(|placement| self.with_placement(placement))
+            .collect();
+
+        let mut hole_board = self.clone();
+        hole_board.mark(Position::from(position));
+
+        Some((boards, position, hole_board))
+    }
+
+    /// Finds the position we want to tile next, and applies `f` to each of the ways of placing
+    /// a tile from the collection over that position
+    fn map_fitting_placements<T, F>(
+        &self,
+        tile_collection: &TileCollection,
+        options: &SearchOptions,
+        f: F,
+    ) -> Vec<T>
+    where
+        F: FnMut(&Placement) -> T,
+    {
+        let position = match options.cell_selector.select(self, tile_collection) {
+            Some(_) if options.prune_regions && !self.regions_coverable(tile_collection) => {
+                return Vec::new()
+            }
+            Some(position) => position,
+            None => return Vec::new(),
+        };
+
+        let table = tile_collection.placement_table(self.width, self.height, self.topology);
+
+        table
+            .covering(position)
+            .filter(|placement| placement.fits(self))
+            .map(f)
+            .collect()
+    }
+
+    /// Returns a copy of this board with the given placement (which should fit) marked
+    fn with_placement(&self, placement: &Placement) -> RectangularBoard {
+        let mut child_board = self.clone();
+
+        for (index, word) in placement.mask() {
+            child_board.mask[*index] |= word;
+        }
+
+        for (row, col) in placement.positions.iter() {
+            child_board.board[*row][*col] = true;
+
+            for (dx, dy) in NEIGHBOURS.iter() {
+                let neighbour = Position::new(*row as isize + dx, *col as isize + dy);
+
+                if let Some(neighbour) = self.wrap(neighbour) {
+                    child_board.counts[neighbour.x as usize][neighbour.y as usize] += 1;
                 }
             }
         }
 
-        // Next, find all the tiles that fit at out best position
-        let mut fitting_tiles = Vec::new();
-
-        if let Some((i, j)) = largest_position {
-            for tile in tile_collection.iter() {
-                for start_index in 0..=tile.directions.len() {
-                    if let Some(tp) =
-                        self.tile_fits_at_position(tile, Position::from((i, j)), start_index)
-                    {
-                        // Really we should be using a HashSet for fitting_tiles, but it's annoying
-                        // to hash a HashSet, so we just check for containment here instead
-                        if !fitting_tiles.contains(&tp) {
-                            fitting_tiles.push(tp);
+        child_board
+    }
+
+    /// Determines whether every connected region of unmarked positions has an area
+    /// which could be covered by tiles from the collection
+    fn regions_coverable(&self, tile_collection: &TileCollection) -> bool {
+        let mut seen = vec![vec![false; self.width]; self.height];
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.board[i][j] || seen[i][j] {
+                    continue;
+                }
+
+                // flood fill the region containing this position
+                let mut area = 0;
+                let mut stack = vec![Position::from((i, j))];
+                seen[i][j] = true;
+
+                while let Some(p) = stack.pop() {
+                    area += 1;
+
+                    for (dx, dy) in NEIGHBOURS.iter() {
+                        let neighbour = match self.wrap(Position::new(p.x + dx, p.y + dy)) {
+                            Some(neighbour) => neighbour,
+                            None => continue,
+                        };
+
+                        if !self.is_marked(neighbour)
+                            && !seen[neighbour.x as usize][neighbour.y as usize]
+                        {
+                            seen[neighbour.x as usize][neighbour.y as usize] = true;
+                            stack.push(neighbour);
                         }
                     }
                 }
+
+                if !tile_collection.can_cover_area(area) {
+                    return false;
+                }
             }
         }
 
-        // For each fitting tile we find, return the corresponding board
-        fitting_tiles
-            .into_iter()
-            .map(|tp| {
-                let mut child_board = self.clone();
-                child_board.mark_tile_at_position(tp);
-                child_board
-            })
-            .collect()
+        true
+    }
+
+    /// Returns the number of marked positions next to the given `(row, col)` position, plus
+    /// one if the position is on an edge of the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    ///
+    /// let board = RectangularBoard::new(3, 2).place_tile_at(&[(0, 0)]).unwrap();
+    /// assert_eq!(board.count((0, 1)), 2);
+    /// assert_eq!(board.count((1, 1)), 1);
+    /// ```
+    pub fn count(&self, position: (usize, usize)) -> usize {
+        self.counts[position.0][position.1]
+    }
+
+    /// Returns the number of ways of placing a tile from the collection over the given `(row, col)`
+    /// position, counting no further than `limit`
+    pub fn num_placements_covering(
+        &self,
+        position: (usize, usize),
+        tile_collection: &TileCollection,
+        limit: usize,
+    ) -> usize {
+        let table = tile_collection.placement_table(self.width, self.height, self.topology);
+
+        table
+            .covering(position)
+            .filter(|placement| placement.fits(self))
+            .take(limit)
+            .count()
+    }
+
+    /// Returns the marked positions as a bitmask, where `(row, col)` is bit `row * width + col`
+    /// (i.e. bit `(row * width + col) % 64` of word `(row * width + col) / 64`)
+    pub fn mask(&self) -> &[u64] {
+        &self.mask
+    }
+
+    /// Returns all the ways of placing a tile from the collection over the given `(row, col)`
+    /// position, each given by its shape and the positions it covers (in row-major order).  This
+    /// is used to build a `PlacementTable`.
+    ///
+    /// Each tile is translated so that one of its cells lies on the position, and then every cell
+    /// is mapped onto the board separately.  Walking from cell to cell instead would go wrong once
+    /// the tile crosses an edge which flips the board over.
+    pub(crate) fn placements_covering(
+        &self,
+        position: (usize, usize),
+        tile_collection: &TileCollection,
+    ) -> Vec<(usize, Vec<(usize, usize)>)> {
+        let mut placements = Vec::new();
+
+        if self.board[position.0][position.1] {
+            return placements;
+        }
+
+        for (shape, tile) in tile_collection.iter_with_shapes() {
+            let cells = tile.cells();
+
+            for (anchor_row, anchor_col) in cells.iter() {
+                let row_offset = position.0 as isize - anchor_row;
+                let col_offset = position.1 as isize - anchor_col;
+
+                let positions: Option<Vec<_>> = cells
+                    .iter()
+                    .map(|(row, col)| {
+                        self.wrap(Position::new(row + row_offset, col + col_offset))
+                            .filter(|p| !self.is_marked(*p))
+                            .map(|p| (p.x as usize, p.y as usize))
+                    })
+                    .collect();
+
+                let mut positions = match positions {
+                    Some(positions) => positions,
+                    None => continue,
+                };
+                positions.sort_unstable();
+                positions.dedup();
+
+                // on a small board which wraps around, a tile could overlap itself
+                if positions.len() != cells.len() || !tile_collection.allows(shape, &positions) {
+                    continue;
+                }
+
+                if !placements.iter().any(|(_, p)| *p == positions) {
+                    placements.push((shape, positions));
+                }
+            }
+        }
+
+        placements
     }
 
     fn is_marked(&self, p: Position) -> bool {
@@ -193,7 +975,41 @@ impl RectangularBoard {
         p.x >= 0 && (p.x as usize) < self.height && p.y >= 0 && (p.y as usize) < self.width
     }
 
-    fn move_in_direction(&self, p: Position, direction: Direction) -> Position {
+    /// Maps a position which may be off the board to the position it is glued to
+    /// (see `Topology::wrap`), or None if it really is off the board
+    fn wrap(&self, p: Position) -> Option<Position> {
+        self.topology
+            .wrap((p.x, p.y), self.width, self.height)
+            .map(Position::from)
+    }
+
+    /// Returns the `(row, col)` position next to the given one in the given direction, taking
+    /// the topology of the board into account.  Returns None if we would leave the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::Direction;
+    /// use dcc_tiler::topology::Topology;
+    ///
+    /// let board = RectangularBoard::new(3, 2);
+    /// assert_eq!(board.neighbour((0, 2), Direction::Right), None);
+    ///
+    /// let board = board.with_topology(Topology::Mobius);
+    /// assert_eq!(board.neighbour((0, 2), Direction::Right), Some((1, 0)));
+    /// assert_eq!(board.neighbour((0, 2), Direction::UpRight), None);
+    /// ```
+    pub fn neighbour(
+        &self,
+        position: (usize, usize),
+        direction: Direction,
+    ) -> Option<(usize, usize)> {
+        self.move_in_direction(Position::from(position), direction)
+            .map(|p| (p.x as usize, p.y as usize))
+    }
+
+    fn move_in_direction(&self, p: Position, direction: Direction) -> Option<Position> {
         let mut row = p.x;
         let mut col = p.y;
 
@@ -216,67 +1032,72 @@ impl RectangularBoard {
             _ => 0,
         };
 
-        Position::new(row, col)
+        self.wrap(Position::new(row, col))
+    }
+
+    /// Unmarks a position, undoing `mark`
+    fn unmark(&mut self, p: Position) {
+        for (dx, dy) in NEIGHBOURS.iter() {
+            if let Some(neighbour) = self.wrap(Position::new(p.x + dx, p.y + dy)) {
+                self.counts[neighbour.x as usize][neighbour.y as usize] -= 1;
+            }
+        }
+
+        self.board[p.x as usize][p.y as usize] = false;
+
+        let bit = p.x as usize * self.width + p.y as usize;
+        self.mask[bit / 64] &= !(1 << (bit % 64));
     }
 
-    /// Tests whether the specified tile fits at the specified board position.
-    /// If it does, then return Some(TilePosition)
+    /// Returns the ways of placing down a tile from the collection over the position we would
+    /// tile next (as for `place_tile`), each given by the `(row, col)` positions it covers.
+    ///
+    /// Together with `mark_positions` and `unmark_positions`, this lets us search for tilings
+    /// without making a copy of the board for every tile we place down.
     ///
     /// # Examples
     ///
     /// ```
-    /// // Example code here
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::selector::SearchOptions;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let mut board = RectangularBoard::new(2, 2);
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    ///
+    /// let options = SearchOptions::default();
+    ///
+    /// let placements = board.next_placements(&tiles, &options);
+    /// assert_eq!(placements.len(), 2);
+    ///
+    /// board.mark_positions(&placements[0]);
+    /// assert_eq!(board.next_placements(&tiles, &options).len(), 1);
+    ///
+    /// board.unmark_positions(&placements[0]);
+    /// assert_eq!(board, RectangularBoard::new(2, 2));
     /// ```
-    fn tile_fits_at_position(
+    pub fn next_placements(
         &self,
-        tile: &Tile,
-        position: Position,
-        start_index: usize,
-    ) -> Option<TilePosition> {
-        // make sure our start index isn't too large
-        assert!(start_index <= tile.directions.len());
-
-        let mut current_position = position;
-
-        let valid_and_unmarked = |p: Position| self.is_valid(p) && !self.is_marked(p);
-
-        if !valid_and_unmarked(current_position) {
-            return None;
-        }
-
-        let mut covered = HashSet::new();
-        covered.insert(current_position);
-
-        // move backwards from start_index - 1
-        for i in (0..start_index).rev() {
-            current_position =
-                self.move_in_direction(current_position, tile.directions[i].opposite());
-
-            if !valid_and_unmarked(current_position) {
-                return None;
-            }
-            covered.insert(current_position);
-        }
-
-        let mut current_position = position;
-
-        // now move forwards after the start index
-        for i in start_index..tile.directions.len() {
-            current_position = self.move_in_direction(current_position, tile.directions[i]);
-
-            if !valid_and_unmarked(current_position) {
-                return None;
-            }
+        tile_collection: &TileCollection,
+        options: &SearchOptions,
+    ) -> Vec<Vec<(usize, usize)>> {
+        self.map_fitting_placements(tile_collection, options, |placement| {
+            placement.positions.clone()
+        })
+    }
 
-            covered.insert(current_position);
+    /// Marks the given `(row, col)` positions, which should be unmarked
+    pub fn mark_positions(&mut self, positions: &[(usize, usize)]) {
+        for position in positions {
+            self.mark(Position::from(*position));
         }
-
-        Some(TilePosition::new(covered))
     }
 
-    fn mark_tile_at_position(&mut self, tp: TilePosition) {
-        for position in tp.covered {
-            self.mark(position);
+    /// Unmarks the given `(row, col)` positions, which should have been marked by `mark_positions`
+    /// (or by placing down a tile)
+    pub fn unmark_positions(&mut self, positions: &[(usize, usize)]) {
+        for position in positions {
+            self.unmark(Position::from(*position));
         }
     }
 }
@@ -296,6 +1117,9 @@ impl fmt::Debug for RectangularBoard {
     }
 }
 
+/// The offsets of the four positions sharing a side with a position
+const NEIGHBOURS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 struct Position {
     x: isize,
@@ -319,20 +1143,3 @@ impl Position {
         Position { x, y }
     }
 }
-
-#[derive(Eq, Clone)]
-struct TilePosition {
-    covered: HashSet<Position>,
-}
-
-impl TilePosition {
-    pub fn new(covered: HashSet<Position>) -> Self {
-        TilePosition { covered }
-    }
-}
-
-impl PartialEq for TilePosition {
-    fn eq(&self, other: &TilePosition) -> bool {
-        self.covered == other.covered
-    }
-}
@@ -0,0 +1,364 @@
+use crate::board::RectangularBoard;
+use crate::tile::TileCollection;
+use num::Integer;
+use std::collections::HashSet;
+use std::fmt;
+
+// The largest stripe period and modulus we try when searching for a certificate
+const MAX_PERIOD: usize = 4;
+const MAX_MODULUS: u64 = 4;
+
+/// A way of colouring the positions of a board, with colours numbered from zero
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Colouring {
+    /// Every position has the same colour
+    Uniform,
+
+    /// Colour `(row, col)` by `(row + col) % 2`, like a chessboard
+    Checkerboard,
+
+    /// Colour `(row, col)` by `row % period`
+    Rows(usize),
+
+    /// Colour `(row, col)` by `col % period`
+    Columns(usize),
+
+    /// Colour `(row, col)` by `(row + col) % period`
+    Diagonals(usize),
+
+    /// Colour `(row, col)` by `(row - col) % period`
+    AntiDiagonals(usize),
+}
+
+impl Colouring {
+    /// Returns the number of colours used
+    pub fn num_colours(self) -> usize {
+        match self {
+            Colouring::Uniform => 1,
+            Colouring::Checkerboard => 2,
+            Colouring::Rows(period)
+            | Colouring::Columns(period)
+            | Colouring::Diagonals(period)
+            | Colouring::AntiDiagonals(period) => period,
+        }
+    }
+
+    /// Returns the colour of the given `(row, col)` position
+    pub fn colour(self, (row, col): (usize, usize)) -> usize {
+        match self {
+            Colouring::Uniform => 0,
+            Colouring::Checkerboard => (row + col) % 2,
+            Colouring::Rows(period) => row % period,
+            Colouring::Columns(period) => col % period,
+            Colouring::Diagonals(period) => (row + col) % period,
+            Colouring::AntiDiagonals(period) => (row + period - col % period) % period,
+        }
+    }
+
+    /// The colourings we try when searching for a certificate, simplest first
+    fn candidates() -> Vec<Colouring> {
+        let mut candidates = vec![Colouring::Checkerboard];
+
+        for period in 2..=MAX_PERIOD {
+            candidates.push(Colouring::Rows(period));
+            candidates.push(Colouring::Columns(period));
+
+            // diagonal stripes with period 2 are just a checkerboard
+            if period > 2 {
+                candidates.push(Colouring::Diagonals(period));
+                candidates.push(Colouring::AntiDiagonals(period));
+            }
+        }
+
+        candidates
+    }
+}
+
+impl fmt::Display for Colouring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Colouring::Uniform => write!(f, "a single colour"),
+            Colouring::Checkerboard => write!(f, "a checkerboard"),
+            Colouring::Rows(period) => write!(f, "{} colours of horizontal stripes", period),
+            Colouring::Columns(period) => write!(f, "{} colours of vertical stripes", period),
+            Colouring::Diagonals(period) => write!(f, "{} colours of diagonal stripes", period),
+            Colouring::AntiDiagonals(period) => {
+                write!(f, "{} colours of anti-diagonal stripes", period)
+            }
+        }
+    }
+}
+
+/// A proof that a board can't be tiled, by a colouring argument.
+///
+/// Each colour is given a weight, and every way of placing down a tile covers positions whose
+/// weights add up to one of `tile_sums` (modulo `modulus`).  Adding these up over the tiles of a
+/// tiling (of which there are `num_tiles`, if the tiles all have the same size) we find the total
+/// weights which a tiling can cover, none of which is the total weight of the board.
+#[derive(Clone, Debug)]
+pub struct Certificate {
+    pub width: usize,
+    pub height: usize,
+
+    /// The positions which were already marked on the board
+    pub marked: Vec<Vec<bool>>,
+
+    pub colouring: Colouring,
+
+    /// The weight of each colour
+    pub weights: Vec<u64>,
+
+    pub modulus: u64,
+
+    /// The total weights (modulo `modulus`) which can be covered by placing down a tile
+    pub tile_sums: Vec<u64>,
+
+    /// The number of tiles in any tiling, or None if the tiles have different sizes
+    pub num_tiles: Option<usize>,
+
+    /// The total weights (modulo `modulus`) which can be covered by a tiling
+    pub reachable: Vec<u64>,
+
+    /// The total weight (modulo `modulus`) of the unmarked positions of the board
+    pub board_sum: u64,
+}
+
+impl Certificate {
+    /// Searches for a colouring argument showing that the board can't be tiled.  After checking
+    /// the number of positions, we try a checkerboard, and horizontal, vertical and diagonal stripes
+    /// of a few colours, with every choice of weights modulo some small numbers.  Returns None if no such argument is found,
+    /// which doesn't mean that the board can be tiled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::certificate::{Certificate, Colouring};
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// // the mutilated chessboard
+    /// let mut marks = vec![vec![false; 8]; 8];
+    /// marks[0][0] = true;
+    /// marks[7][7] = true;
+    /// let board = RectangularBoard::from_marks(marks);
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    /// let certificate = Certificate::find(&board, &tiles).unwrap();
+    /// assert_eq!(certificate.colouring, Colouring::Checkerboard);
+    ///
+    /// // every domino covers one black and one white position, so a tiling of the
+    /// // full chessboard can't be ruled out in this way
+    /// assert!(Certificate::find(&RectangularBoard::new(8, 8), &tiles).is_none());
+    /// ```
+    pub fn find(board: &RectangularBoard, tiles: &TileCollection) -> Option<Self> {
+        let positions: Vec<(usize, usize)> = (0..board.height)
+            .flat_map(|row| (0..board.width).map(move |col| (row, col)))
+            .filter(|(row, col)| !board.board[*row][*col])
+            .collect();
+
+        let placements = all_placements(board, tiles);
+
+        let areas: HashSet<usize> = tiles.iter().map(|tile| tile.cells().len()).collect();
+        let area_gcd = areas.iter().fold(0, |gcd, area| area.gcd(&gcd));
+
+        let mut certificate = Certificate {
+            width: board.width,
+            height: board.height,
+            marked: board.board.clone(),
+            colouring: Colouring::Uniform,
+            weights: vec![1],
+            modulus: area_gcd as u64,
+            tile_sums: vec![0],
+            num_tiles: None,
+            reachable: vec![0],
+            board_sum: (positions.len() % area_gcd.max(1)) as u64,
+        };
+
+        // the simplest argument: the number of positions isn't a multiple of the tile size
+        if area_gcd > 1 && certificate.board_sum != 0 {
+            return Some(certificate);
+        }
+
+        if areas.len() == 1 {
+            certificate.num_tiles = Some(positions.len() / area_gcd);
+        }
+
+        for colouring in Colouring::candidates() {
+            let num_colours = colouring.num_colours();
+
+            // the number of positions of each colour covered by a tile or by the board
+            let colour_counts = |positions: &[(usize, usize)]| {
+                let mut counts = vec![0; num_colours];
+
+                for position in positions {
+                    counts[colouring.colour(*position)] += 1;
+                }
+
+                counts
+            };
+
+            let tile_counts: HashSet<Vec<u64>> = placements
+                .iter()
+                .map(|placement| colour_counts(placement))
+                .collect();
+            let board_counts = colour_counts(&positions);
+
+            for modulus in 2..=MAX_MODULUS {
+                let mut weights = vec![0; num_colours];
+
+                // try every choice of weights, like counting in base `modulus`
+                while let Some(i) = weights.iter().position(|w| *w + 1 < modulus) {
+                    weights[i] += 1;
+                    for weight in weights[..i].iter_mut() {
+                        *weight = 0;
+                    }
+
+                    let sum = |counts: &Vec<u64>| {
+                        counts
+                            .iter()
+                            .zip(weights.iter())
+                            .map(|(c, w)| c * w)
+                            .sum::<u64>()
+                            % modulus
+                    };
+
+                    let mut tile_sums: Vec<u64> = tile_counts.iter().map(sum).collect();
+                    tile_sums.sort_unstable();
+                    tile_sums.dedup();
+
+                    let reachable = reachable_sums(&tile_sums, certificate.num_tiles, modulus);
+                    let board_sum = sum(&board_counts);
+
+                    if !reachable.contains(&board_sum) {
+                        certificate.colouring = colouring;
+                        certificate.weights = weights.clone();
+                        certificate.modulus = modulus;
+                        certificate.tile_sums = tile_sums;
+                        certificate.reachable = reachable;
+                        certificate.board_sum = board_sum;
+
+                        return Some(certificate);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the weight of the given `(row, col)` position
+    pub fn weight(&self, position: (usize, usize)) -> u64 {
+        self.weights[self.colouring.colour(position)]
+    }
+}
+
+/// Returns the total weights (modulo `modulus`) which can be covered by `num_tiles` tiles, each
+/// covering a weight in `tile_sums`.  If `num_tiles` is None, any number of tiles may be used.
+fn reachable_sums(tile_sums: &[u64], num_tiles: Option<usize>, modulus: u64) -> Vec<u64> {
+    // the sums which can be covered by some number of tiles, as a bitmask
+    let step = |reachable: u64| {
+        let mut next = 0;
+
+        for sum in (0..modulus).filter(|sum| reachable & (1 << sum) != 0) {
+            for tile_sum in tile_sums {
+                next |= 1 << ((sum + tile_sum) % modulus);
+            }
+        }
+
+        next
+    };
+
+    let mut reachable = 1;
+
+    match num_tiles {
+        Some(num_tiles) => {
+            // the reachable sums are eventually periodic in the number of tiles,
+            // so we can skip ahead once we've seen them repeat
+            let mut seen = vec![reachable];
+            let mut i = 0;
+
+            while i < num_tiles {
+                reachable = step(reachable);
+                i += 1;
+
+                if let Some(start) = seen.iter().position(|r| *r == reachable) {
+                    let period = i - start;
+                    reachable = seen[start + (num_tiles - i) % period];
+                    break;
+                }
+
+                seen.push(reachable);
+            }
+        }
+        None => loop {
+            let next = reachable | step(reachable);
+
+            if next == reachable {
+                break;
+            }
+            reachable = next;
+        },
+    }
+
+    (0..modulus)
+        .filter(|sum| reachable & (1 << sum) != 0)
+        .collect()
+}
+
+impl fmt::Display for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |values: &[u64]| {
+            let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            values.join(", ")
+        };
+
+        writeln!(
+            f,
+            "Colour the board with {}, with weights {} (mod {}).",
+            self.colouring,
+            join(&self.weights),
+            self.modulus
+        )?;
+        write!(
+            f,
+            "Every tile covers a total weight of {} (mod {}), ",
+            join(&self.tile_sums),
+            self.modulus
+        )?;
+
+        match self.num_tiles {
+            Some(num_tiles) => write!(f, "so a tiling by {} tiles ", num_tiles)?,
+            None => write!(f, "so a tiling ")?,
+        }
+
+        write!(
+            f,
+            "covers {} (mod {}), but the board has a total weight of {} (mod {}).",
+            join(&self.reachable),
+            self.modulus,
+            self.board_sum,
+            self.modulus
+        )
+    }
+}
+
+/// Returns every way of placing down a tile on the unmarked positions of the board,
+/// each given by the `(row, col)` positions it covers
+fn all_placements(board: &RectangularBoard, tiles: &TileCollection) -> Vec<Vec<(usize, usize)>> {
+    let table = tiles.placement_table(board.width, board.height, board.topology);
+    let mut placements = Vec::new();
+
+    // every placement covers some position, so we find each of them at least once
+    for row in 0..board.height {
+        for col in 0..board.width {
+            for placement in table.covering((row, col)) {
+                // only count each placement at the first position it covers
+                if placement.positions[0] == (row, col) && placement.fits(board) {
+                    placements.push(placement.positions.clone());
+                }
+            }
+        }
+    }
+
+    placements
+}
@@ -1,10 +1,22 @@
+use dcc_tiler::binary::{open_graph, write_graph, MAGIC};
 use dcc_tiler::board::RectangularBoard;
-use dcc_tiler::tile::{Tile, TileCollection};
+use dcc_tiler::export::{to_dot, to_graphml, NodeLabel};
+use dcc_tiler::flip::FlipGraph;
+use dcc_tiler::height::HeightFunction;
+use dcc_tiler::selector::{FewestPlacements, FirstEmpty, Frontier, MaxConstrained};
+use dcc_tiler::sweep::RectangleSweep;
+use dcc_tiler::tile::{normalize_positions, Tile, TileCollection};
+use dcc_tiler::tiling::Tiling;
+use dcc_tiler::topology::Topology;
 
 use clap::{Parser, ValueEnum};
+use num::Zero;
 
-use dcc_tiler::render::render_single_tiling_from_vec;
-use std::io::Result;
+use dcc_tiler::render::{
+    render_certificate, render_heatmap, render_height_function, render_packing,
+    render_single_tiling_from_vec, render_tiling,
+};
+use std::io::{BufRead, Result, Write};
 use tiler::Tiler;
 
 #[derive(Debug, Copy, Clone, ValueEnum)]
@@ -13,6 +25,13 @@ pub enum BoardType {
     Rectangle,
     LBoard,
     TBoard,
+    Shape,
+    AztecDiamond,
+    Staircase,
+    YoungDiagram,
+    HoleySquare,
+    Cross,
+    MutilatedChessboard,
 }
 
 #[derive(Debug, Copy, Clone, ValueEnum)]
@@ -23,6 +42,72 @@ pub enum TileType {
     BoxTile,
 }
 
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum GraphFormat {
+    Json,
+    Dot,
+    Graphml,
+    Binary,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum GraphLabel {
+    Board,
+    Depth,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum CellSelectorType {
+    MaxConstrained,
+    FirstEmpty,
+    FewestPlacements,
+    Frontier,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum TopologyType {
+    Plane,
+    Cylinder,
+    Torus,
+    Mobius,
+    Klein,
+}
+
+impl From<TopologyType> for Topology {
+    fn from(topology: TopologyType) -> Self {
+        match topology {
+            TopologyType::Plane => Topology::Plane,
+            TopologyType::Cylinder => Topology::Cylinder,
+            TopologyType::Torus => Topology::Torus,
+            TopologyType::Mobius => Topology::Mobius,
+            TopologyType::Klein => Topology::Klein,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum SweepFormat {
+    Table,
+    Csv,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum HeightFormat {
+    Csv,
+    Json,
+    Svg,
+}
+
+impl HeightFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            HeightFormat::Csv => "csv",
+            HeightFormat::Json => "json",
+            HeightFormat::Svg => "svg",
+        }
+    }
+}
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
@@ -41,13 +126,86 @@ struct Cli {
     #[arg(
         long = "scale",
         default_value_t = 1,
-        help = "The board scale ot use, if using an LBoard"
+        help = "The board scale to use: each box of the board is replaced by a scale^2 box"
     )]
     board_scale: usize,
 
+    #[arg(
+        long,
+        value_name = "ROW,COL;...",
+        value_parser = parse_positions,
+        required_if_eq("board_type", "Shape"),
+        help = "The positions making up the board, if using a Shape board, e.g. 0,1;1,0;1,1;1,2;2,1 for the X pentomino (may be repeated)"
+    )]
+    shape: Vec<Vec<(usize, usize)>>,
+
+    #[arg(
+        long,
+        value_name = "LENGTH,...",
+        value_delimiter = ',',
+        required_if_eq("board_type", "YoungDiagram"),
+        help = "The row lengths of the board, from top to bottom, if using a YoungDiagram board"
+    )]
+    partition: Vec<usize>,
+
+    #[arg(
+        long,
+        default_value_t = 2,
+        help = "The side length of the hole in the middle of the board, if using a HoleySquare board"
+    )]
+    hole: usize,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = TopologyType::Plane,
+        help = "How to glue the edges of the board together: the left and right edges (cylinder), flipped over (mobius), or also the top and bottom edges (torus, klein)"
+    )]
+    topology: TopologyType,
+
     #[arg(long, value_enum, default_value_t = TileType::LTile, help = "The type of tile to use")]
     tile_type: TileType,
 
+    #[arg(
+        long = "extra-tile",
+        value_name = "TYPE:SIZE",
+        value_parser = parse_tile_spec,
+        help = "An additional tile shape to use, e.g. BoxTile or LTile:2 (may be repeated)"
+    )]
+    extra_tiles: Vec<(TileType, usize)>,
+
+    #[arg(
+        long = "fix",
+        value_name = "ROW,COL;...",
+        value_parser = parse_positions,
+        help = "Only consider tilings containing a tile covering these positions (may be repeated)"
+    )]
+    fixed_placements: Vec<Vec<(usize, usize)>>,
+
+    #[arg(
+        long = "forbid",
+        value_name = "ROW,COL;...",
+        value_parser = parse_positions,
+        help = "Don't allow a tile covering exactly these positions (may be repeated)"
+    )]
+    forbidden_placements: Vec<Vec<(usize, usize)>>,
+
+    #[arg(
+        long = "forbid-position",
+        value_name = "SHAPE:ROW,COL",
+        value_parser = parse_shape_position,
+        help = "Don't allow tiles of a shape (0 for --tile-type, 1 for the first --extra-tile, ...) to cover a position (may be repeated)"
+    )]
+    forbidden_positions: Vec<(usize, (usize, usize))>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Load a tilings graph previously saved with --graph (as JSON or binary), instead of generating one (the board options are ignored)",
+        conflicts_with_all = ["scaling", "fixed_placements"]
+    )]
+    load_graph: Option<String>,
+
     #[arg(
         short,
         long,
@@ -60,7 +218,7 @@ struct Cli {
     #[arg(
         short,
         long,
-        help = "Render all tilings to a specified file in ZIP format",
+        help = "Render all tilings (or all samples, with --sample) to a specified file in ZIP format",
         conflicts_with = "single",
         conflicts_with = "count",
         conflicts_with = "graph",
@@ -77,6 +235,30 @@ struct Cli {
     )]
     count: bool,
 
+    #[arg(
+        long,
+        help = "Also count tilings up to the symmetries of the board",
+        requires = "count"
+    )]
+    up_to_symmetry: bool,
+
+    #[arg(
+        long,
+        help = "Count tilings by the number of tiles of each shape used",
+        requires = "count",
+        conflicts_with = "up_to_symmetry"
+    )]
+    polynomial: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "If there are no tilings, search for a colouring argument proving this, and render it to FILE",
+        requires = "count",
+        conflicts_with_all = ["up_to_symmetry", "polynomial"]
+    )]
+    certificate: Option<String>,
+
     #[arg(
         short,
         long,
@@ -86,6 +268,112 @@ struct Cli {
     )]
     graph: bool,
 
+    #[arg(
+        long,
+        help = "Compute the graph of tilings connected by local moves, and report its connectivity, diameter and degrees",
+        conflicts_with_all = ["single", "all", "count", "graph", "pack", "heatmap", "stats", "scaling"]
+    )]
+    flip_graph: bool,
+
+    #[arg(
+        long,
+        value_name = "ROWSxCOLS",
+        default_value = "2x2",
+        value_parser = parse_window,
+        help = "The size of the window re-tiled by a local move"
+    )]
+    window: (usize, usize),
+
+    #[arg(
+        long,
+        value_name = "STEPS",
+        help = "Sample a random tiling by applying this many random local moves to some tiling",
+        conflicts_with_all = ["single", "count", "graph", "pack", "heatmap", "flip_graph", "stats", "scaling"]
+    )]
+    sample: Option<usize>,
+
+    #[arg(
+        long,
+        default_value_t = 1,
+        requires = "sample",
+        help = "The number of tilings to sample, which requires --all if more than 1"
+    )]
+    num_samples: usize,
+
+    #[arg(
+        long,
+        requires = "sample",
+        help = "The random seed to use when sampling"
+    )]
+    seed: Option<u64>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        help = "Output the height function of the domino tiling found with --single or --sample, instead of the tiling"
+    )]
+    height_function: Option<HeightFormat>,
+
+    #[arg(
+        long,
+        help = "Print statistics about the tilings graph",
+        conflicts_with_all = ["single", "all", "count", "graph", "pack", "heatmap", "scaling"]
+    )]
+    stats: bool,
+
+    #[arg(
+        long,
+        help = "Remove the boards which can't be extended to a tiling from the tilings graph",
+        conflicts_with_all = ["single", "pack", "scaling"]
+    )]
+    prune: bool,
+
+    #[arg(
+        long,
+        help = "Give up on boards with a region of empty positions whose area can't be made up from the tile areas"
+    )]
+    prune_regions: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CellSelectorType::MaxConstrained,
+        help = "How to choose the position to tile next when searching for tilings"
+    )]
+    cell_selector: CellSelectorType,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = GraphFormat::Json,
+        help = "The format to output the tilings graph in"
+    )]
+    graph_format: GraphFormat,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = GraphLabel::Board,
+        help = "How to label nodes when outputting the tilings graph as DOT or GraphML"
+    )]
+    graph_label: GraphLabel,
+
+    #[arg(
+        short,
+        long,
+        help = "Compute a packing using as many tiles as possible",
+        conflicts_with_all = ["single", "all", "count", "graph", "scaling"]
+    )]
+    pack: bool,
+
+    #[arg(
+        long,
+        help = "Render the probability of each position being covered by each tile orientation",
+        conflicts_with_all = ["single", "all", "count", "graph", "pack", "scaling"]
+    )]
+    heatmap: bool,
+
     #[arg(
         long,
         help = "Compute the tiling count for different value of the scale parameter",
@@ -94,34 +382,225 @@ struct Cli {
         conflicts_with = "single"
     )]
     scaling: bool,
+
+    #[arg(
+        long,
+        help = "Decide which rectangles with sides at most the board size can be tiled, marking each as proven by area, transpose, composition or search",
+        conflicts_with_all = [
+            "single", "all", "count", "graph", "pack", "heatmap", "flip_graph", "stats", "scaling",
+            "sample", "certificate", "fixed_placements", "load_graph", "topology", "board_type",
+            "board_scale", "shape", "partition", "hole", "forbidden_placements", "forbidden_positions",
+            "cell_selector", "prune_regions"
+        ]
+    )]
+    sweep: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = SweepFormat::Table,
+        requires = "sweep",
+        help = "The format of the output of --sweep"
+    )]
+    sweep_format: SweepFormat,
 }
 
 mod tiler;
 
+/// Parses a tile specification of the form `TYPE:SIZE`, where the size must be positive.  The
+/// size of a `BoxTile` doesn't matter, so it may be left out.
+fn parse_tile_spec(spec: &str) -> std::result::Result<(TileType, usize), String> {
+    let (tile_type, size) = match spec.split_once(':') {
+        Some((tile_type, size)) => (tile_type, Some(size)),
+        None => (spec, None),
+    };
+    let tile_type = TileType::from_str(tile_type, false)?;
+
+    let size = match (tile_type, size) {
+        (TileType::BoxTile, None) => 0,
+        (_, None) => return Err(format!("missing size for '{}', e.g. {}:2", spec, spec)),
+        (tile_type, Some(size)) => match size.parse() {
+            Ok(0) if !matches!(tile_type, TileType::BoxTile) => {
+                return Err(format!("tile size must be positive in '{}'", spec))
+            }
+            Ok(size) => size,
+            Err(_) => return Err(format!("invalid tile size '{}'", size)),
+        },
+    };
+
+    Ok((tile_type, size))
+}
+
+/// Parses a `(row, col)` position of the form `ROW,COL`
+fn parse_position(position: &str) -> std::result::Result<(usize, usize), String> {
+    let invalid = || format!("invalid position '{}'", position);
+    let (row, col) = position.split_once(',').ok_or_else(invalid)?;
+
+    Ok((
+        row.trim().parse().map_err(|_| invalid())?,
+        col.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// Parses a list of positions of the form `ROW,COL;ROW,COL;...`
+fn parse_positions(positions: &str) -> std::result::Result<Vec<(usize, usize)>, String> {
+    positions.split(';').map(parse_position).collect()
+}
+
+/// Parses a window size of the form `ROWSxCOLS`
+fn parse_window(window: &str) -> std::result::Result<(usize, usize), String> {
+    let invalid = || format!("invalid window size '{}'", window);
+    let (rows, cols) = window.split_once('x').ok_or_else(invalid)?;
+    let size = (
+        rows.trim().parse().map_err(|_| invalid())?,
+        cols.trim().parse().map_err(|_| invalid())?,
+    );
+
+    if size.0 == 0 || size.1 == 0 {
+        return Err(invalid());
+    }
+
+    Ok(size)
+}
+
+/// Parses a shape and position of the form `SHAPE:ROW,COL`
+fn parse_shape_position(spec: &str) -> std::result::Result<(usize, (usize, usize)), String> {
+    let (shape, position) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("invalid shape position '{}'", spec))?;
+
+    Ok((
+        shape
+            .parse()
+            .map_err(|_| format!("invalid shape '{}'", shape))?,
+        parse_position(position)?,
+    ))
+}
+
+/// Formats the height function of a tiling, exiting with an error if it isn't a domino tiling
+fn format_height_function(tiling: &Tiling, format: HeightFormat) -> String {
+    let heights = match HeightFunction::from_tiling(tiling) {
+        Some(heights) => heights,
+        None => {
+            eprintln!(
+                "Height functions are only defined for domino tilings of boards without holes"
+            );
+            std::process::exit(1);
+        }
+    };
+
+    match format {
+        HeightFormat::Csv => heights.to_csv(),
+        HeightFormat::Json => heights.to_json(),
+        HeightFormat::Svg => render_height_function(&heights, tiling),
+    }
+}
+
+/// Writes files to a ZIP file, in the same format as `Tiler::render_all_tilings`
+fn write_zip<I: Iterator<Item = String>>(filename: &str, files: I, extension: &str) -> Result<()> {
+    let file = std::fs::File::create(filename)?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    for (i, contents) in files.enumerate() {
+        zip.start_file(format!("{}.{}", i, extension), Default::default())?;
+        zip.write_all(contents.as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn set_cell_selector(tiler: &mut Tiler, cell_selector: CellSelectorType) {
+    match cell_selector {
+        CellSelectorType::MaxConstrained => tiler.set_cell_selector(MaxConstrained),
+        CellSelectorType::FirstEmpty => tiler.set_cell_selector(FirstEmpty),
+        CellSelectorType::FewestPlacements => tiler.set_cell_selector(FewestPlacements),
+        CellSelectorType::Frontier => tiler.set_cell_selector(Frontier),
+    }
+}
+
+fn make_tile(tile_type: TileType, tile_size: usize) -> Tile {
+    match tile_type {
+        TileType::LTile => Tile::l_tile(tile_size),
+        TileType::TTile => Tile::t_tile(tile_size),
+        TileType::BoxTile => Tile::box_tile(),
+    }
+}
+
 fn main() -> Result<()> {
     let cli: Cli = Cli::parse();
 
     let board_width = cli.width.unwrap_or(cli.board_size);
 
     // Create a colletion of tiles based on the tile(s) specified by the user
-    let tile = match cli.tile_type {
-        TileType::LTile => Tile::l_tile(cli.tile_size),
-        TileType::TTile => Tile::t_tile(cli.tile_size),
-        TileType::BoxTile => Tile::box_tile(),
-    };
+    let mut shapes = vec![make_tile(cli.tile_type, cli.tile_size)];
+    shapes.extend(
+        cli.extra_tiles
+            .iter()
+            .map(|(tile_type, tile_size)| make_tile(*tile_type, *tile_size)),
+    );
+
+    let mut tiles = TileCollection::from_shapes(shapes);
+
+    for placement in cli.forbidden_placements.iter() {
+        tiles.forbid_placement(placement);
+    }
+
+    for (shape, position) in cli.forbidden_positions.iter() {
+        tiles.forbid_position(*shape, *position);
+    }
 
-    let tiles = TileCollection::from(tile);
+    if cli.partition.windows(2).any(|pair| pair[0] < pair[1]) {
+        eprintln!("The row lengths given to --partition can't increase");
+        std::process::exit(1);
+    }
+
+    // tilings of boards with glued edges can't be described by a `Tiling`
+    if cli.topology != TopologyType::Plane
+        && (cli.sample.is_some() || cli.flip_graph || cli.heatmap || cli.height_function.is_some())
+    {
+        eprintln!(
+            "--sample, --flip-graph, --heatmap and --height-function require --topology plane"
+        );
+        std::process::exit(1);
+    }
 
     // A closure to create a board based on specified options
-    let make_board =
-        |board_type: BoardType, board_size: usize, board_width: usize, board_scale: usize| {
-            match board_type {
-                BoardType::Rectangle => RectangularBoard::new(board_width, board_size),
-                BoardType::LBoard => RectangularBoard::l_board(board_size, board_scale),
-                BoardType::TBoard => RectangularBoard::t_board(board_size, board_scale),
+    let make_board = |board_type: BoardType,
+                      board_size: usize,
+                      board_width: usize,
+                      board_scale: usize| {
+        let board = match board_type {
+            BoardType::Rectangle => RectangularBoard::new(board_width, board_size),
+            BoardType::LBoard => RectangularBoard::l_board(board_size, 1),
+            BoardType::TBoard => RectangularBoard::t_board(board_size, 1),
+            BoardType::Shape => {
+                let positions: Vec<_> = cli.shape.concat();
+                RectangularBoard::from_cells(&normalize_positions(&positions))
             }
+            BoardType::AztecDiamond => RectangularBoard::aztec_diamond(board_size),
+            BoardType::Staircase => RectangularBoard::staircase(board_size),
+            BoardType::YoungDiagram => RectangularBoard::young_diagram(&cli.partition),
+            BoardType::HoleySquare => RectangularBoard::holey_square(board_size, cli.hole),
+            // the arms are one box wide unless a width is given
+            BoardType::Cross => RectangularBoard::cross(board_size, cli.width.unwrap_or(1)),
+            BoardType::MutilatedChessboard => RectangularBoard::mutilated_chessboard(board_size),
         };
 
+        board.scale(board_scale).with_topology(cli.topology.into())
+    };
+
+    if cli.sweep {
+        let sweep = RectangleSweep::new(&tiles, cli.board_size);
+
+        match cli.sweep_format {
+            SweepFormat::Table => print!("{}", sweep.to_table()),
+            SweepFormat::Csv => print!("{}", sweep.to_csv()),
+        }
+
+        return Ok(());
+    }
+
     if cli.scaling {
         // we deal with scaling separately to appease the borrow checker
         let mut board_scale: usize = 1;
@@ -131,24 +610,169 @@ fn main() -> Result<()> {
                 tiles.clone(),
                 make_board(cli.board_type, cli.board_size, board_width, board_scale),
             );
+            set_cell_selector(&mut tiler, cli.cell_selector);
+            tiler.set_region_pruning(cli.prune_regions);
             println!("scale({}), {} tilings", board_scale, tiler.count_tilings());
             board_scale += 1;
         }
     } else {
-        let board = make_board(cli.board_type, cli.board_size, board_width, cli.board_scale);
-        let mut tiler = Tiler::new(tiles, board);
+        let mut tiler = if let Some(filename) = cli.load_graph.as_ref() {
+            let mut file = std::io::BufReader::new(std::fs::File::open(filename)?);
+
+            let graph = if file.fill_buf()?.starts_with(MAGIC) {
+                open_graph(filename)?
+            } else {
+                serde_json::from_reader(file)?
+            };
+
+            match Tiler::from_graph(tiles, graph) {
+                Ok(tiler) => tiler,
+                Err(e) => {
+                    eprintln!("Unable to load {}: {}", filename, e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            let board = make_board(cli.board_type, cli.board_size, board_width, cli.board_scale);
+            let mut tiler = Tiler::new(tiles, board);
+            set_cell_selector(&mut tiler, cli.cell_selector);
+            tiler.set_region_pruning(cli.prune_regions);
+            tiler
+        };
+
+        // forbidding positions which aren't on the board is almost certainly a mistake
+        let board = tiler.initial_board();
+        let off_board = cli
+            .forbidden_placements
+            .iter()
+            .flatten()
+            .chain(cli.forbidden_positions.iter().map(|(_, position)| position))
+            .find(|(row, col)| *row >= board.height || *col >= board.width);
+
+        if let Some(position) = off_board {
+            eprintln!(
+                "Forbidden position {:?} isn't on the {}x{} board",
+                position, board.width, board.height
+            );
+            std::process::exit(1);
+        }
+
+        for placement in cli.fixed_placements.iter() {
+            if !tiler.fix_placement(placement) {
+                eprintln!("Unable to place a tile covering {:?}", placement);
+                std::process::exit(1);
+            }
+        }
+
+        if cli.prune {
+            let graph = tiler.graph();
+            let total = graph.read().unwrap().len();
+            let removed = tiler.prune_graph();
+
+            eprintln!("Pruned {} dead-end states (out of {})", removed, total);
+        }
 
-        if cli.count {
+        if cli.count && cli.polynomial {
+            let polynomial = tiler.count_tilings_polynomial();
+            println!("{} tilings found", polynomial.sum_of_coefficients());
+            println!("generating polynomial: {}", polynomial);
+        } else if cli.count && cli.up_to_symmetry {
+            let (total, orbits) = tiler.count_tilings_up_to_symmetry();
+            println!("{} tilings found ({} up to symmetry)", total, orbits);
+        } else if cli.count {
             // just do a quick tilings count - no need to generate the tiling graph
-            println!("{} tilings found", tiler.count_tilings());
+            let count = tiler.count_tilings();
+            println!("{} tilings found", count);
+
+            if let Some(filename) = cli.certificate.as_ref().filter(|_| count.is_zero()) {
+                match tiler.certificate() {
+                    Some(certificate) => {
+                        println!("{}", certificate);
+                        std::fs::write(filename, render_certificate(&certificate))?;
+                    }
+                    None => println!("No colouring argument found"),
+                }
+            }
         } else if cli.single {
             let tiling = tiler.get_single_tiling(1000);
 
             if let Some(tiling) = tiling {
-                println!("{}", render_single_tiling_from_vec(tiling.iter().collect()));
+                match cli.height_function {
+                    Some(format) => println!(
+                        "{}",
+                        format_height_function(&Tiling::from_boards(&tiling), format)
+                    ),
+                    None => println!("{}", render_single_tiling_from_vec(tiling.iter().collect())),
+                }
             } else {
                 println!("No tilings found!");
             }
+        } else if cli.pack {
+            let (packing, holes) = tiler.get_max_packing();
+
+            eprintln!(
+                "Packed {} tiles, leaving {} positions uncovered",
+                packing.len() - 1 - holes.len(),
+                holes.len()
+            );
+            println!("{}", render_packing(packing.iter().collect(), &holes));
+        } else if cli.flip_graph {
+            let tilings = tiler.all_tilings();
+            let flip_graph = FlipGraph::new(tilings, tiler.tiles(), cli.window);
+
+            println!("tilings: {}", flip_graph.len());
+            println!("local moves: {}", flip_graph.num_edges());
+            println!("connected components: {}", flip_graph.components().len());
+
+            match flip_graph.diameter() {
+                Some(diameter) => println!("diameter: {}", diameter),
+                None => println!("diameter: infinite (not connected)"),
+            }
+
+            println!();
+            println!("degree,tilings");
+            for (degree, count) in flip_graph.degree_distribution() {
+                println!("{},{}", degree, count);
+            }
+        } else if cli.stats {
+            let board_graph = tiler.graph();
+            print!("{}", board_graph.read().unwrap().stats());
+        } else if cli.heatmap {
+            match tiler.heatmap() {
+                Some(heatmap) if !heatmap.total.is_zero() => {
+                    eprintln!("{} tilings found", heatmap.total);
+                    println!("{}", render_heatmap(&heatmap));
+                }
+                _ => println!("No tilings found!"),
+            }
+        } else if let Some(steps) = cli.sample {
+            if cli.num_samples > 1 && cli.all.is_none() {
+                eprintln!("Use --all to write more than one sample to a ZIP file");
+                std::process::exit(1);
+            }
+
+            let seed = cli.seed.unwrap_or_else(rand::random);
+            eprintln!("Sampling with seed {}", seed);
+
+            match tiler.sample_tilings(steps, cli.num_samples, cli.window, seed) {
+                Some(samples) => {
+                    let height_function = cli.height_function;
+                    let files = samples.iter().map(|tiling| match height_function {
+                        Some(format) => format_height_function(tiling, format),
+                        None => render_tiling(tiling),
+                    });
+                    let extension = cli.height_function.map_or("svg", HeightFormat::extension);
+
+                    if let Some(filename) = cli.all {
+                        write_zip(&filename, files, extension)?;
+                    } else {
+                        for file in files {
+                            println!("{}", file);
+                        }
+                    }
+                }
+                None => println!("No tilings found!"),
+            }
         } else if let Some(filename) = cli.all {
             tiler.render_all_tilings(&filename)?;
         } else if cli.graph {
@@ -157,7 +781,23 @@ fn main() -> Result<()> {
             {
                 let board_graph = board_graph.read().unwrap();
 
-                println!("{}", serde_json::to_string(&*board_graph).unwrap());
+                let label = match cli.graph_label {
+                    GraphLabel::Board => NodeLabel::Board,
+                    GraphLabel::Depth => NodeLabel::Depth,
+                };
+
+                match cli.graph_format {
+                    GraphFormat::Json => {
+                        println!("{}", serde_json::to_string(&*board_graph).unwrap())
+                    }
+                    GraphFormat::Dot => print!("{}", to_dot(&board_graph, label)),
+                    GraphFormat::Graphml => print!("{}", to_graphml(&board_graph, label)),
+                    GraphFormat::Binary => {
+                        let mut stdout = std::io::BufWriter::new(std::io::stdout().lock());
+                        write_graph(&board_graph, &mut stdout)?;
+                        stdout.flush()?;
+                    }
+                }
             }
         }
     }
@@ -1,20 +1,76 @@
 use dcc_tiler::board::RectangularBoard;
+use dcc_tiler::certificate::Certificate;
 use dcc_tiler::graph::BoardGraph;
+use dcc_tiler::heatmap::Heatmap;
+use dcc_tiler::polynomial::Polynomial;
+use dcc_tiler::sampler::FlipSampler;
+use dcc_tiler::selector::{CellSelector, SearchOptions};
+use dcc_tiler::symmetry::Symmetry;
 use dcc_tiler::tile::TileCollection;
+use dcc_tiler::tiling::Tiling;
 use num::{BigUint, One, Zero};
 
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
+use std::ops::AddAssign;
 use std::sync::{Arc, RwLock};
 
 use dcc_tiler::render::render_single_tiling_from_vec;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use std::io::{Result, Write};
 
+/// A quantity attached to each partial tiling, which we accumulate as we count tilings
+trait TilingWeight: Clone + Send + Sync + for<'a> AddAssign<&'a Self> {
+    /// The weight of an empty collection of tilings
+    fn empty() -> Self;
+
+    /// The weight of the initial board
+    fn initial() -> Self;
+
+    /// Returns the weight obtained from this weight by placing down a tile of the given shape
+    fn place(&self, shape: usize) -> Self;
+}
+
+impl TilingWeight for BigUint {
+    fn empty() -> Self {
+        BigUint::zero()
+    }
+
+    fn initial() -> Self {
+        BigUint::one()
+    }
+
+    fn place(&self, _shape: usize) -> Self {
+        self.clone()
+    }
+}
+
+impl TilingWeight for Polynomial {
+    fn empty() -> Self {
+        Polynomial::zero()
+    }
+
+    fn initial() -> Self {
+        Polynomial::one()
+    }
+
+    fn place(&self, shape: usize) -> Self {
+        self.mul_var(shape)
+    }
+}
+
 pub struct Tiler {
     tiles: TileCollection,
     initial_board: RectangularBoard,
+    options: SearchOptions,
     graph: Option<Arc<RwLock<BoardGraph>>>,
+
+    // The boards obtained by placing down each of our fixed tiles in turn (not including
+    // the initial board), and the shape of each fixed tile
+    fixed_boards: Vec<RectangularBoard>,
+    fixed_shapes: Vec<usize>,
 }
 
 impl Tiler {
@@ -22,10 +78,99 @@ impl Tiler {
         Tiler {
             tiles,
             initial_board,
+            options: SearchOptions::default(),
             graph: None,
+            fixed_boards: Vec::new(),
+            fixed_shapes: Vec::new(),
         }
     }
 
+    /// Creates a tiler from a previously generated tiling graph, so that we don't need to
+    /// search for tilings again.  The initial board is the first node of the graph.
+    ///
+    /// Any tiles which were fixed when the graph was generated are treated as part of the
+    /// board, rather than as tiles.  Returns an error if the graph has no nodes.
+    pub fn from_graph(
+        tiles: TileCollection,
+        graph: BoardGraph,
+    ) -> std::result::Result<Self, String> {
+        let initial_board = graph
+            .get_node(0)
+            .ok_or_else(|| String::from("graph has no initial board"))?
+            .clone();
+
+        Ok(Tiler {
+            tiles,
+            initial_board,
+            options: SearchOptions::default(),
+            graph: Some(Arc::new(RwLock::new(graph))),
+            fixed_boards: Vec::new(),
+            fixed_shapes: Vec::new(),
+        })
+    }
+
+    /// Returns the board we're tiling, with any fixed tiles already placed down
+    pub fn initial_board(&self) -> &RectangularBoard {
+        &self.initial_board
+    }
+
+    pub fn tiles(&self) -> &TileCollection {
+        &self.tiles
+    }
+
+    /// Sets how we choose the position to tile next when searching for tilings
+    pub fn set_cell_selector<S: CellSelector + 'static>(&mut self, cell_selector: S) {
+        self.options.cell_selector = Arc::new(cell_selector);
+
+        // any graph we've generated was built using the old selector
+        self.graph = None;
+    }
+
+    /// Sets whether to give up on boards with a region of unmarked positions whose area can't
+    /// be covered by our tiles
+    pub fn set_region_pruning(&mut self, prune_regions: bool) {
+        self.options.prune_regions = prune_regions;
+
+        // any graph we've generated may contain boards we'd now prune
+        self.graph = None;
+    }
+
+    /// Requires every tiling to contain a tile covering the given `(row, col)` positions.
+    ///
+    /// Returns false (leaving the tiler unchanged) if no tile in our collection has this shape,
+    /// if the placement is forbidden, or if the tile doesn't fit on the board.
+    pub fn fix_placement(&mut self, positions: &[(usize, usize)]) -> bool {
+        let shape = match self.tiles.shape_of(positions) {
+            Some(shape) => shape,
+            None => return false,
+        };
+
+        if !self.tiles.allows(shape, positions) {
+            return false;
+        }
+
+        match self.initial_board.place_tile_at(positions) {
+            Some(board) => {
+                self.fixed_boards
+                    .push(std::mem::replace(&mut self.initial_board, board));
+                self.fixed_shapes.push(shape);
+
+                // any graph we've generated is now out of date
+                self.graph = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Prepends the boards obtained by placing down our fixed tiles to a sequence
+    /// of boards starting at our initial board, so that the fixed tiles get rendered
+    fn with_fixed_tiles(&self, boards: Vec<RectangularBoard>) -> Vec<RectangularBoard> {
+        let mut all_boards = self.fixed_boards.clone();
+        all_boards.extend(boards);
+        all_boards
+    }
+
     pub fn count_tilings(&mut self) -> BigUint {
         // Use a boardgraph, if available.
         if self.graph.is_some() {
@@ -35,34 +180,112 @@ impl Tiler {
         }
     }
 
+    /// Searches for a colouring argument showing that our initial board (including any
+    /// fixed tiles) can't be tiled
+    pub fn certificate(&self) -> Option<Certificate> {
+        Certificate::find(&self.initial_board, &self.tiles)
+    }
+
+    /// Counts tilings, both in total and up to the symmetries of the board.
+    ///
+    /// The second count is the number of orbits of tilings under the group of board symmetries
+    /// which also preserve our tile collection.  By Burnside's lemma this is the average, over the
+    /// group, of the number of tilings fixed by each symmetry.
+    pub fn count_tilings_up_to_symmetry(&mut self) -> (BigUint, BigUint) {
+        let total = self.count_tilings();
+
+        let symmetries: Vec<_> = self
+            .initial_board
+            .symmetries()
+            .into_iter()
+            .filter(|s| self.tiles.is_invariant_under(*s))
+            .filter(|s| {
+                self.tiles.forbidden_invariant_under(
+                    *s,
+                    self.initial_board.width,
+                    self.initial_board.height,
+                )
+            })
+            .collect();
+
+        let mut fixed = BigUint::zero();
+
+        for symmetry in symmetries.iter() {
+            if *symmetry == Symmetry::Identity {
+                fixed += &total;
+            } else {
+                fixed += self.count_tilings_by::<BigUint, _>(|b| {
+                    b.place_tile_symmetric(&self.tiles, &self.options, *symmetry)
+                        .into_iter()
+                        .map(|board| (0, board))
+                        .collect()
+                });
+            }
+        }
+
+        (total, fixed / symmetries.len())
+    }
+
+    /// Computes the generating polynomial of our tilings.
+    ///
+    /// The coefficient of `x0^a0 x1^a1 ...` is the number of tilings using `a0` tiles
+    /// of the first shape in our tile collection, `a1` tiles of the second shape, and so on.
+    pub fn count_tilings_polynomial(&self) -> Polynomial {
+        let polynomial: Polynomial =
+            self.count_tilings_by(|b| b.place_tile_with_shapes(&self.tiles, &self.options));
+
+        // account for the tiles we placed down in advance
+        self.fixed_shapes
+            .iter()
+            .fold(polynomial, |polynomial, shape| polynomial.mul_var(*shape))
+    }
+
     fn count_tilings_quick(&self) -> BigUint {
+        self.count_tilings_by(|b| b.place_tile_with_shapes(&self.tiles, &self.options))
+    }
+
+    /// Counts the tilings of our initial board, using `place_tile` to compute the boards
+    /// (and the shape of the tile placed) obtained from a given board by placing down a tile
+    fn count_tilings_by<W, F>(&self, place_tile: F) -> W
+    where
+        W: TilingWeight,
+        F: Fn(&RectangularBoard) -> Vec<(usize, RectangularBoard)> + Sync,
+    {
+        // a board which is already covered has exactly one tiling, the empty one
+        if self.initial_board.is_all_marked() {
+            return W::initial();
+        }
+
         // we keep the counter behind an Arc<RwLock<>>
         let mut counter = HashMap::new();
-        counter.insert(self.initial_board.clone(), num::BigUint::one());
+        counter.insert(self.initial_board.clone(), W::initial());
         let mut counter = Arc::new(RwLock::new(counter));
 
         // our working stack
         let mut stack = HashSet::new();
         stack.insert(self.initial_board.clone());
 
-        let completed_board = Arc::new(RwLock::new(Vec::new()));
+        // Complete tilings may be reached after different numbers of steps (e.g. when tiles have
+        // different sizes), so we add up the completed counts as we go
+        let mut total = W::empty();
 
         while !stack.is_empty() {
+            let completed_board = Arc::new(RwLock::new(HashSet::new()));
+
             let handles = stack
                 .par_iter()
                 .map(|b| {
                     let current_count = &counter.read().unwrap()[b];
 
-                    let boards = b.place_tile(&self.tiles);
+                    let boards = place_tile(b);
 
                     let mut next_boards = HashSet::new();
                     let mut completed_boards = HashSet::new();
                     let mut count_updates = HashMap::new();
 
-                    for board in boards {
-                        *count_updates
-                            .entry(board.clone())
-                            .or_insert_with(num::BigUint::zero) += current_count;
+                    for (shape, board) in boards {
+                        *count_updates.entry(board.clone()).or_insert_with(W::empty) +=
+                            &current_count.place(shape);
 
                         if board.is_all_marked() {
                             completed_boards.insert(board);
@@ -93,10 +316,8 @@ impl Tiler {
 
                         // update the counts
                         for (board, count) in count_updates {
-                            let entry = counter_write
-                                .entry(board)
-                                .or_insert_with(num::BigUint::zero);
-                            (*entry) += count;
+                            let entry = counter_write.entry(board).or_insert_with(W::empty);
+                            (*entry) += &count;
                         }
                     }
 
@@ -106,22 +327,22 @@ impl Tiler {
                         // because having a completed board occurs so infrequently
                         {
                             let mut completed_board_write = completed_board.write().unwrap();
-                            completed_board_write.push(board);
+                            completed_board_write.insert(board);
                         }
                     }
                 });
 
             // unwrap our stack
             stack = Arc::try_unwrap(step_stack).unwrap().into_inner().unwrap();
-        }
 
-        let completed_board = completed_board.read().unwrap();
+            let counter = counter.read().unwrap();
 
-        if let Some(board) = completed_board.last() {
-            counter.read().unwrap()[board].clone()
-        } else {
-            num::BigUint::zero()
+            for board in completed_board.read().unwrap().iter() {
+                total += &counter[board];
+            }
         }
+
+        total
     }
 
     fn count_tilings_from_graph(&self) -> BigUint {
@@ -130,57 +351,31 @@ impl Tiler {
 
         // if the graph doesn't have any complete tilings,
         // then we don't have to do any work
-        let complete_board_index = g.get_complete_index();
-
-        if complete_board_index.is_none() {
+        if g.get_complete_index().is_none() {
             return BigUint::zero();
         }
 
-        let mut count_map = HashMap::new();
-        count_map.insert(0, BigUint::one());
-
-        // now work through the stack
-        let mut stack = HashSet::new();
-        stack.insert(0);
-
-        while !stack.is_empty() {
-            let mut next_stack = HashSet::new();
-
-            for board_index in stack {
-                let c = count_map[&board_index].clone();
-
-                if let Some(edges) = g.get_edges(board_index) {
-                    for edge in edges {
-                        let entry = count_map.entry(*edge).or_insert_with(BigUint::zero);
-                        (*entry) += c.clone();
-
-                        next_stack.insert(*edge);
-                    }
-                }
-            }
-
-            stack = next_stack;
-        }
-
-        if let Some(res) = count_map.remove(&complete_board_index.unwrap()) {
-            res
-        } else {
-            BigUint::zero()
-        }
+        // the number of ways of getting from the initial board to a complete board
+        g.backward_counts().swap_remove(0)
     }
 
-    #[allow(dead_code, clippy::map_entry)]
+    #[allow(dead_code)]
     fn generate_graph(&mut self) {
         let mut graph = BoardGraph::new();
         graph.add_node(self.initial_board.clone());
 
-        let graph = Arc::new(RwLock::new(graph));
+        // a board which is already covered is its own (empty) tiling
+        let mut stack = if self.initial_board.is_all_marked() {
+            graph.mark_node_as_complete(0);
+            Vec::new()
+        } else {
+            vec![0]
+        };
 
-        let mut stack = vec![0];
+        let graph = Arc::new(RwLock::new(graph));
 
         while !stack.is_empty() {
             let mut next_iteration = Vec::new();
-            let mut board_map: HashMap<RectangularBoard, usize> = HashMap::new();
 
             for (board_index, child_boards) in stack
                 .into_par_iter()
@@ -192,7 +387,7 @@ impl Tiler {
                         board_index,
                         if let Some(board) = g.get_node(board_index) {
                             // now for each board, place a tile at some position,
-                            board.place_tile(&self.tiles)
+                            board.place_tile(&self.tiles, &self.options)
                         } else {
                             Vec::new()
                         },
@@ -206,15 +401,11 @@ impl Tiler {
                 for board in child_boards {
                     let complete = board.is_all_marked();
 
-                    // We don't want to use an entry here because it would mean
-                    // having to clone our board every single time, even if the board
-                    // was already in our hashmap
-                    let child_index = if board_map.contains_key(&board) {
-                        board_map[&board]
-                    } else {
-                        let index = g.add_node(board.clone());
-                        board_map.insert(board, index);
-                        index
+                    // The same board can be reached from many others (possibly by placing down
+                    // different numbers of tiles), but we only want to expand it once
+                    let (child_index, is_new) = match g.find_node(&board) {
+                        Some(index) => (index, false),
+                        None => (g.add_node(board), true),
                     };
 
                     g.add_edge(board_index, child_index);
@@ -222,7 +413,7 @@ impl Tiler {
                     if complete {
                         // mark this as a finished node in our graph
                         g.mark_node_as_complete(child_index);
-                    } else {
+                    } else if is_new {
                         next_iteration.push(child_index);
                     }
                 }
@@ -233,6 +424,15 @@ impl Tiler {
         self.graph = Some(graph);
     }
 
+    /// Removes the boards from our tiling graph (generating it, if necessary) which can't
+    /// be extended to a tiling.  Returns the number of boards removed.
+    pub fn prune_graph(&mut self) -> usize {
+        let graph = self.graph();
+        let mut graph = graph.write().expect("Unable to write graph");
+
+        graph.prune()
+    }
+
     pub fn graph(&mut self) -> Arc<RwLock<BoardGraph>> {
         // If the graph doesn't exist already, generate it
         if self.graph.is_none() {
@@ -243,6 +443,68 @@ impl Tiler {
         Arc::clone(self.graph.as_ref().unwrap())
     }
 
+    /// Computes, for each position and tile orientation, the number of tilings in which the
+    /// position is covered by a tile with that orientation
+    pub fn heatmap(&mut self) -> Option<Heatmap> {
+        let graph = self.graph();
+        let graph = graph.read().expect("Unable to read graph");
+
+        let mut heatmap = Heatmap::from_graph(&graph, &self.tiles)?;
+
+        // every tiling contains our fixed tiles
+        for tile in self.fixed_tiles() {
+            heatmap.add_fixed_tile(&tile);
+        }
+
+        Some(heatmap)
+    }
+
+    /// Returns the `(row, col)` positions covered by each of our fixed tiles
+    fn fixed_tiles(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut boards = self.fixed_boards.clone();
+        boards.push(self.initial_board.clone());
+
+        boards
+            .windows(2)
+            .map(|pair| pair[0].changed_positions(&pair[1]))
+            .collect()
+    }
+
+    /// Samples tilings using a `FlipSampler`, starting at a tiling found by `Tiling::search`.
+    /// We take `num_samples` samples, running the chain for `steps` steps before each of them.
+    ///
+    /// Returns None if our initial board can't be tiled.
+    pub fn sample_tilings(
+        &self,
+        steps: usize,
+        num_samples: usize,
+        window: (usize, usize),
+        seed: u64,
+    ) -> Option<Vec<Tiling>> {
+        let tiling = Tiling::search(&self.initial_board, &self.tiles)?;
+        let fixed_tiles = self.fixed_tiles();
+
+        let mut sampler = FlipSampler::new(
+            tiling,
+            self.tiles.clone(),
+            window,
+            StdRng::seed_from_u64(seed),
+        );
+
+        let samples = (0..num_samples)
+            .map(|_| {
+                sampler.run(steps);
+
+                // put back our fixed tiles, which the sampler doesn't know about
+                let mut tiling = sampler.tiling().clone();
+                tiling.retile(&fixed_tiles);
+                tiling
+            })
+            .collect();
+
+        Some(samples)
+    }
+
     // Maybe change String to Into<PathBuf>?
     pub fn render_all_tilings(&mut self, output_filename: &str) -> Result<()> {
         let graph = self.graph();
@@ -258,11 +520,11 @@ impl Tiler {
 
             let mut stack = vec![(complete, vec![board])];
 
-            while !stack.is_empty() {
-                let (index, boards) = stack.pop().unwrap();
-
+            while let Some((index, boards)) = stack.pop() {
                 if index == 0 {
-                    // render this tiling
+                    // render this tiling, including any fixed tiles
+                    let mut boards = boards;
+                    boards.extend(self.fixed_boards.iter().rev());
                     let tiling = render_single_tiling_from_vec(boards);
 
                     // filename for this tiling
@@ -288,13 +550,47 @@ impl Tiler {
         Ok(())
     }
 
+    /// Returns every tiling of our initial board.  Fixed tiles are treated as part of the
+    /// board, rather than as tiles.
+    pub fn all_tilings(&mut self) -> Vec<Tiling> {
+        let graph = self.graph();
+        let graph = graph.read().expect("Unable to read graph");
+        let mut tilings = Vec::new();
+
+        if let Some(complete) = graph.get_complete_index() {
+            // work backwards from the complete board, as for render_all_tilings
+            let mut stack = vec![(complete, vec![graph.get_node(complete).unwrap().clone()])];
+
+            while let Some((index, boards)) = stack.pop() {
+                if index == 0 {
+                    let mut boards = boards;
+                    boards.reverse();
+                    tilings.push(Tiling::from_boards(&boards));
+                } else {
+                    for e in graph.get_rev_edges(index).unwrap() {
+                        let mut new_boards = boards.clone();
+                        new_boards.push(graph.get_node(*e).unwrap().clone());
+
+                        stack.push((*e, new_boards));
+                    }
+                }
+            }
+        }
+
+        tilings
+    }
+
     pub fn get_single_tiling(&mut self, limit: usize) -> Option<Vec<RectangularBoard>> {
+        if self.initial_board.is_all_marked() {
+            return Some(self.with_fixed_tiles(vec![self.initial_board.clone()]));
+        }
+
         let mut stack = vec![vec![self.initial_board.clone()]];
         let mut completed_tilings = Vec::new();
 
         while let Some(tvec) = stack.pop() {
             let current_board = tvec.last().unwrap();
-            let fitting_tiles = current_board.place_tile(&self.tiles);
+            let fitting_tiles = current_board.place_tile(&self.tiles, &self.options);
 
             for board in fitting_tiles {
                 let is_all_marked = board.is_all_marked();
@@ -314,6 +610,97 @@ impl Tiler {
             }
         }
 
-        completed_tilings.choose(&mut rand::thread_rng()).cloned()
+        completed_tilings
+            .choose(&mut rand::thread_rng())
+            .cloned()
+            .map(|tiling| self.with_fixed_tiles(tiling))
+    }
+
+    /// Finds a packing of our initial board using as many tiles as possible, or equivalently,
+    /// leaving as few positions uncovered as possible.
+    ///
+    /// Returns the sequence of boards making up the packing (as for `get_single_tiling`),
+    /// together with the `(row, col)` positions which are left uncovered.
+    pub fn get_max_packing(&self) -> (Vec<RectangularBoard>, HashSet<(usize, usize)>) {
+        let unmarked = self
+            .initial_board
+            .board
+            .iter()
+            .flatten()
+            .filter(|marked| !**marked)
+            .count();
+
+        // The number of uncovered positions is congruent to the number of unmarked positions
+        // modulo the gcd of the tile sizes, so we only need to try every gcd-th number of holes
+        let gcd = self
+            .tiles
+            .iter()
+            .map(|tile| tile.cells().len())
+            .fold(0, num::integer::gcd)
+            .max(1);
+
+        let mut failed = HashMap::new();
+        let mut holes = unmarked % gcd;
+
+        loop {
+            let mut boards = vec![self.initial_board.clone()];
+            let mut hole_positions = HashSet::new();
+
+            if self.pack(&mut boards, &mut hole_positions, holes, &mut failed) {
+                return (self.with_fixed_tiles(boards), hole_positions);
+            }
+
+            holes += gcd;
+        }
+    }
+
+    /// Tries to extend a partial packing to a packing of the entire board, leaving at
+    /// most `budget` positions uncovered.  Returns whether this was successful.
+    ///
+    /// The map `failed` keeps track of the largest budget with which we've failed
+    /// to complete each board.
+    fn pack(
+        &self,
+        boards: &mut Vec<RectangularBoard>,
+        holes: &mut HashSet<(usize, usize)>,
+        budget: usize,
+        failed: &mut HashMap<RectangularBoard, usize>,
+    ) -> bool {
+        let board = boards.last().unwrap().clone();
+
+        let (child_boards, hole, hole_board) =
+            match board.place_tile_or_hole(&self.tiles, &self.options) {
+                Some(children) => children,
+                None => return true,
+            };
+
+        if failed.get(&board).is_some_and(|b| *b >= budget) {
+            return false;
+        }
+
+        for child_board in child_boards {
+            boards.push(child_board);
+
+            if self.pack(boards, holes, budget, failed) {
+                return true;
+            }
+
+            boards.pop();
+        }
+
+        if budget > 0 {
+            boards.push(hole_board);
+            holes.insert(hole);
+
+            if self.pack(boards, holes, budget - 1, failed) {
+                return true;
+            }
+
+            holes.remove(&hole);
+            boards.pop();
+        }
+
+        failed.insert(board, budget);
+        false
     }
 }
@@ -0,0 +1,209 @@
+use num::{BigUint, One, Zero};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// An exact cover problem: a set of cells, numbered from zero, together with a list of
+/// placements, each covering some of the cells.  A solution is a set of placements covering
+/// every cell exactly once.
+///
+/// This is the part of tiling which doesn't care about the shape of the cells, so it's used to
+/// tile boards on grids other than the square grid (see the `hex` module, for example), where
+/// each placement is a way of placing down a tile.
+#[derive(Clone, Debug)]
+pub struct ExactCover {
+    num_cells: usize,
+    placements: Vec<Vec<usize>>,
+
+    // For each cell, the indices of the placements covering it
+    covering: Vec<Vec<usize>>,
+}
+
+impl ExactCover {
+    /// Creates an exact cover problem with the given number of cells.  Placements covering
+    /// a cell more than once, or covering no cells, are ignored.
+    pub fn new(num_cells: usize, placements: Vec<Vec<usize>>) -> Self {
+        let mut covering = vec![Vec::new(); num_cells];
+
+        for (i, placement) in placements.iter().enumerate() {
+            let distinct: HashSet<_> = placement.iter().collect();
+
+            if placement.is_empty() || distinct.len() != placement.len() {
+                continue;
+            }
+
+            for cell in placement {
+                covering[*cell].push(i);
+            }
+        }
+
+        ExactCover {
+            num_cells,
+            placements,
+            covering,
+        }
+    }
+
+    /// Creates the exact cover problem of covering the given positions (which should be
+    /// sorted) by the given placements, each a list of positions
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a placement covers a position which isn't in `positions`.
+    pub fn from_positions<T: Ord>(positions: &[T], placements: &[Vec<T>]) -> Self {
+        ExactCover::new(
+            positions.len(),
+            placements
+                .iter()
+                .map(|placement| {
+                    placement
+                        .iter()
+                        .map(|p| positions.binary_search(p).unwrap())
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.num_cells
+    }
+
+    /// Returns the cells covered by the placement with the given index
+    pub fn placement(&self, index: usize) -> &[usize] {
+        &self.placements[index]
+    }
+
+    /// Returns the first cell which isn't covered, given the covered cells as a bitmask
+    fn first_uncovered(&self, covered: &[u64]) -> Option<usize> {
+        (0..self.num_cells).find(|cell| covered[cell / 64] & (1 << (cell % 64)) == 0)
+    }
+
+    /// Returns the placements which cover the first uncovered cell without overlapping the
+    /// covered cells, along with the cells covered once they're placed down.  Returns None if
+    /// every cell is covered.
+    fn extensions<'a>(
+        &'a self,
+        covered: &'a [u64],
+    ) -> Option<impl Iterator<Item = (usize, Vec<u64>)> + 'a> {
+        let first = self.first_uncovered(covered)?;
+
+        let extensions = self.covering[first].iter().filter_map(move |i| {
+            let mut next = covered.to_vec();
+
+            for cell in self.placements[*i].iter() {
+                let (word, bit) = (cell / 64, 1 << (cell % 64));
+
+                if next[word] & bit != 0 {
+                    return None;
+                }
+                next[word] |= bit;
+            }
+
+            Some((*i, next))
+        });
+
+        Some(extensions)
+    }
+
+    /// Counts the solutions.  We always cover the first uncovered cell, so the cells should be
+    /// numbered so that nearby cells have nearby numbers (e.g. in row-major order).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::cover::ExactCover;
+    ///
+    /// // the dominoes on a 2x2 square, with cells numbered in row-major order
+    /// let problem = ExactCover::new(4, vec![vec![0, 1], vec![2, 3], vec![0, 2], vec![1, 3]]);
+    /// assert_eq!(problem.count(), 2u32.into());
+    /// ```
+    pub fn count(&self) -> BigUint {
+        // the partial covers with each first uncovered cell, and the number of ways
+        // of reaching them.  Covering a cell only ever increases the first uncovered cell,
+        // so once we've handled one of these layers we never see it again.
+        let mut layers: BTreeMap<usize, HashMap<Vec<u64>, BigUint>> = BTreeMap::new();
+        let mut total = BigUint::zero();
+
+        let initial = vec![0; self.num_cells.div_ceil(64)];
+        layers.entry(0).or_default().insert(initial, BigUint::one());
+
+        while let Some((_, layer)) = layers.pop_first() {
+            for (covered, count) in layer {
+                let extensions = match self.extensions(&covered) {
+                    Some(extensions) => extensions,
+                    None => {
+                        total += count;
+                        continue;
+                    }
+                };
+
+                for (_, next) in extensions {
+                    let first = self.first_uncovered(&next).unwrap_or(self.num_cells);
+
+                    *layers
+                        .entry(first)
+                        .or_default()
+                        .entry(next)
+                        .or_insert_with(BigUint::zero) += &count;
+                }
+            }
+        }
+
+        total
+    }
+
+    /// Finds a solution, as a list of indices of placements, or returns None if there isn't one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::cover::ExactCover;
+    ///
+    /// let problem = ExactCover::new(3, vec![vec![0, 1], vec![1, 2], vec![2]]);
+    /// assert_eq!(problem.find(), Some(vec![0, 2]));
+    ///
+    /// let problem = ExactCover::new(3, vec![vec![0, 1], vec![1, 2]]);
+    /// assert_eq!(problem.find(), None);
+    /// ```
+    pub fn find(&self) -> Option<Vec<usize>> {
+        let mut dead_ends = HashSet::new();
+        let mut solution = Vec::new();
+        let initial = vec![0; self.num_cells.div_ceil(64)];
+
+        if self.find_from(&initial, &mut solution, &mut dead_ends) {
+            Some(solution)
+        } else {
+            None
+        }
+    }
+
+    /// Extends `solution` to a solution, given the cells it covers, remembering the partial
+    /// covers which can't be extended so that we don't try them twice
+    fn find_from(
+        &self,
+        covered: &[u64],
+        solution: &mut Vec<usize>,
+        dead_ends: &mut HashSet<Vec<u64>>,
+    ) -> bool {
+        let extensions = match self.extensions(covered) {
+            Some(extensions) => extensions,
+            None => return true,
+        };
+
+        for (i, next) in extensions {
+            if dead_ends.contains(&next) {
+                continue;
+            }
+
+            solution.push(i);
+
+            if self.find_from(&next, solution, dead_ends) {
+                return true;
+            }
+
+            solution.pop();
+            dead_ends.insert(next);
+        }
+
+        false
+    }
+}
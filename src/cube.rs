@@ -0,0 +1,490 @@
+//! Tilings of boxes by polycubes.
+//!
+//! Cubical cells are indexed by `(layer, row, col)`, with layers increasing away from the viewer
+//! and rows increasing downwards, so each layer is laid out like a `RectangularBoard`.
+
+use crate::cover::ExactCover;
+use num::BigUint;
+
+/// The `(layer, row, col)` position of a cell in a box
+pub type Position3 = (usize, usize, usize);
+
+/// The six directions from a cubical cell to its neighbours
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CubeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+    Front,
+    Back,
+}
+
+impl CubeDirection {
+    pub fn all() -> [CubeDirection; 6] {
+        [
+            CubeDirection::Up,
+            CubeDirection::Down,
+            CubeDirection::Left,
+            CubeDirection::Right,
+            CubeDirection::Front,
+            CubeDirection::Back,
+        ]
+    }
+
+    /// Returns the `(layer, row, col)` offset obtained by moving one step in this direction
+    pub fn offset(self) -> (isize, isize, isize) {
+        match self {
+            CubeDirection::Up => (0, -1, 0),
+            CubeDirection::Down => (0, 1, 0),
+            CubeDirection::Left => (0, 0, -1),
+            CubeDirection::Right => (0, 0, 1),
+            CubeDirection::Front => (-1, 0, 0),
+            CubeDirection::Back => (1, 0, 0),
+        }
+    }
+
+    /// Returns the image of this direction under the given symmetry
+    pub fn transform(self, symmetry: CubeSymmetry) -> Self {
+        let offset = symmetry.apply_offset(self.offset());
+
+        *CubeDirection::all()
+            .iter()
+            .find(|d| d.offset() == offset)
+            .unwrap()
+    }
+}
+
+/// An element of the symmetry group of a cube, which permutes the `(layer, row, col)` axes
+/// and then flips some of them
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CubeSymmetry {
+    // The axis that each new axis is taken from, and whether it's flipped
+    axes: [usize; 3],
+    flips: [bool; 3],
+}
+
+impl CubeSymmetry {
+    /// Returns all 48 symmetries of the cube, starting with the 24 rotations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::cube::CubeSymmetry;
+    ///
+    /// let symmetries = CubeSymmetry::all();
+    /// assert_eq!(symmetries.len(), 48);
+    /// assert_eq!(symmetries.iter().filter(|s| s.is_rotation()).count(), 24);
+    /// ```
+    pub fn all() -> Vec<CubeSymmetry> {
+        let permutations = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+
+        let mut symmetries: Vec<CubeSymmetry> = permutations
+            .iter()
+            .flat_map(|axes| {
+                (0..8).map(move |flips| CubeSymmetry {
+                    axes: *axes,
+                    flips: [flips & 1 != 0, flips & 2 != 0, flips & 4 != 0],
+                })
+            })
+            .collect();
+
+        symmetries.sort_by_key(|s| !s.is_rotation());
+        symmetries
+    }
+
+    /// Determines whether this symmetry is a rotation, rather than a reflection
+    /// (or a rotation followed by a reflection)
+    pub fn is_rotation(self) -> bool {
+        // the sign of the permutation of the axes, times the signs of the flips
+        let odd_permutation = matches!(self.axes, [0, 2, 1] | [1, 0, 2] | [2, 1, 0]);
+        let odd_flips = self.flips.iter().filter(|f| **f).count() % 2 == 1;
+
+        odd_permutation == odd_flips
+    }
+
+    /// Applies this symmetry to a `(layer, row, col)` offset between two cells
+    pub fn apply_offset(self, offset: (isize, isize, isize)) -> (isize, isize, isize) {
+        let offset = [offset.0, offset.1, offset.2];
+        let axis = |i: usize| {
+            if self.flips[i] {
+                -offset[self.axes[i]]
+            } else {
+                offset[self.axes[i]]
+            }
+        };
+
+        (axis(0), axis(1), axis(2))
+    }
+}
+
+/// A polycube, described by a walk through its cells (as for `Tile`)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CubeTile {
+    pub directions: Vec<CubeDirection>,
+}
+
+impl CubeTile {
+    pub fn new(directions: Vec<CubeDirection>) -> Self {
+        CubeTile { directions }
+    }
+
+    /// Returns a tile consisting of a single cube
+    pub fn single() -> Self {
+        CubeTile::new(Vec::new())
+    }
+
+    /// Returns a straight tile consisting of `length` cubes
+    ///
+    /// # Panics
+    ///
+    /// Will panic if length = 0
+    pub fn bar(length: usize) -> Self {
+        assert!(length > 0);
+
+        CubeTile::new(vec![CubeDirection::Right; length - 1])
+    }
+
+    /// Returns the seven pieces of the Soma cube: the bent tricube, followed by the L, T, S,
+    /// branch and two (mirror image) screw tetracubes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::cube::CubeTile;
+    ///
+    /// let pieces = CubeTile::soma();
+    /// let cells: usize = pieces.iter().map(|piece| piece.cells().len()).sum();
+    /// assert_eq!(cells, 27);
+    ///
+    /// // the screws are reflections of one another, but can't be rotated into one another
+    /// assert_eq!(pieces[5].rotations().len(), 12);
+    /// assert!(!pieces[5].rotations().contains(&pieces[6].cells()));
+    /// assert!(pieces[5].orbit().contains(&pieces[6].cells()));
+    /// ```
+    pub fn soma() -> [CubeTile; 7] {
+        use CubeDirection::*;
+
+        [
+            CubeTile::new(vec![Right, Down]),
+            CubeTile::new(vec![Right, Right, Down]),
+            CubeTile::new(vec![Right, Right, Left, Down]),
+            CubeTile::new(vec![Right, Down, Right]),
+            CubeTile::new(vec![Right, Left, Down, Up, Back]),
+            CubeTile::new(vec![Up, Right, Back]),
+            CubeTile::new(vec![Left, Down, Back]),
+        ]
+    }
+
+    /// Returns the image of this tile under the given symmetry
+    pub fn transform(&self, symmetry: CubeSymmetry) -> CubeTile {
+        CubeTile::new(
+            self.directions
+                .iter()
+                .map(|d| d.transform(symmetry))
+                .collect(),
+        )
+    }
+
+    /// Returns the `(layer, row, col)` offsets of the cells making up this tile, translated
+    /// so that the smallest layer, row and column are all zero.  The result is sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::cube::{CubeDirection, CubeTile};
+    ///
+    /// let tile = CubeTile::new(vec![CubeDirection::Front, CubeDirection::Right]);
+    /// assert_eq!(tile.cells(), vec![(0, 0, 0), (0, 0, 1), (1, 0, 0)]);
+    /// ```
+    pub fn cells(&self) -> Vec<(isize, isize, isize)> {
+        let mut current = (0, 0, 0);
+        let mut cells = vec![current];
+
+        for direction in &self.directions {
+            let (dl, dr, dc) = direction.offset();
+            current = (current.0 + dl, current.1 + dr, current.2 + dc);
+            cells.push(current);
+        }
+
+        let min_layer = cells.iter().map(|c| c.0).min().unwrap();
+        let min_row = cells.iter().map(|c| c.1).min().unwrap();
+        let min_col = cells.iter().map(|c| c.2).min().unwrap();
+
+        let mut cells: Vec<_> = cells
+            .into_iter()
+            .map(|(l, r, c)| (l - min_layer, r - min_row, c - min_col))
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+
+    /// Returns the distinct orientations of this tile under the given symmetries,
+    /// each given by its cells (as for `cells`)
+    fn orientations<I: Iterator<Item = CubeSymmetry>>(
+        &self,
+        symmetries: I,
+    ) -> Vec<Vec<(isize, isize, isize)>> {
+        let mut orientations = Vec::new();
+
+        for symmetry in symmetries {
+            let cells = self.transform(symmetry).cells();
+
+            if !orientations.contains(&cells) {
+                orientations.push(cells);
+            }
+        }
+
+        orientations
+    }
+
+    /// Returns the distinct orientations of this tile under the 24 rotations of the cube
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::cube::CubeTile;
+    ///
+    /// assert_eq!(CubeTile::bar(3).rotations().len(), 3);
+    /// assert_eq!(CubeTile::soma()[0].rotations().len(), 12);
+    /// ```
+    pub fn rotations(&self) -> Vec<Vec<(isize, isize, isize)>> {
+        self.orientations(CubeSymmetry::all().into_iter().filter(|s| s.is_rotation()))
+    }
+
+    /// Returns the distinct orientations of this tile under the 48 rotations and reflections
+    /// of the cube
+    pub fn orbit(&self) -> Vec<Vec<(isize, isize, isize)>> {
+        self.orientations(CubeSymmetry::all().into_iter())
+    }
+}
+
+/// A box of cubes, `width` x `height` x `depth`, some of which may be marked
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CubeBoard {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+
+    /// The marked positions, indexed by `[layer][row][col]`
+    pub board: Vec<Vec<Vec<bool>>>,
+}
+
+impl CubeBoard {
+    /// Returns a box `width` cubes wide, `height` cubes tall and `depth` cubes deep
+    pub fn cuboid(width: usize, height: usize, depth: usize) -> Self {
+        CubeBoard::from_marks(vec![vec![vec![false; width]; height]; depth])
+    }
+
+    /// Creates a board from the marked positions of a box, indexed by `[layer][row][col]`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the board is empty.
+    pub fn from_marks(board: Vec<Vec<Vec<bool>>>) -> Self {
+        CubeBoard {
+            width: board[0][0].len(),
+            height: board[0].len(),
+            depth: board.len(),
+            board,
+        }
+    }
+
+    /// Returns the `(layer, row, col)` positions which aren't marked, in order
+    pub fn positions(&self) -> Vec<Position3> {
+        let mut positions = Vec::new();
+
+        for layer in 0..self.depth {
+            for row in 0..self.height {
+                for col in 0..self.width {
+                    if !self.board[layer][row][col] {
+                        positions.push((layer, row, col));
+                    }
+                }
+            }
+        }
+
+        positions
+    }
+
+    /// Returns the given `(layer, row, col)` position if it's an unmarked position of the board
+    fn position(&self, (layer, row, col): (isize, isize, isize)) -> Option<Position3> {
+        if layer < 0 || row < 0 || col < 0 {
+            return None;
+        }
+
+        let (layer, row, col) = (layer as usize, row as usize, col as usize);
+
+        if layer >= self.depth || row >= self.height || col >= self.width {
+            return None;
+        }
+
+        Some((layer, row, col)).filter(|_| !self.board[layer][row][col])
+    }
+
+    /// Returns the position next to the given `(layer, row, col)` position in the given
+    /// direction, or None if that isn't an unmarked position of the board
+    pub fn neighbour(&self, position: Position3, direction: CubeDirection) -> Option<Position3> {
+        let (dl, dr, dc) = direction.offset();
+
+        self.position((
+            position.0 as isize + dl,
+            position.1 as isize + dr,
+            position.2 as isize + dc,
+        ))
+    }
+
+    /// Returns every way of placing down a tile with the given cells (as for `CubeTile::cells`)
+    /// on the unmarked positions of the board
+    fn translations(&self, cells: &[(isize, isize, isize)]) -> Vec<Vec<Position3>> {
+        let first = cells[0];
+
+        // line up the first cell of the tile with each position in turn
+        self.positions()
+            .into_iter()
+            .filter_map(|(layer, row, col)| {
+                cells
+                    .iter()
+                    .map(|(l, r, c)| {
+                        self.position((
+                            layer as isize + l - first.0,
+                            row as isize + r - first.1,
+                            col as isize + c - first.2,
+                        ))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns every way of placing down one of the given tiles (in any orientation, including
+    /// reflections) on the unmarked positions of the board, along with the index of its shape
+    /// in `shapes`
+    pub fn placements(&self, shapes: &[CubeTile]) -> Vec<(usize, Vec<Position3>)> {
+        let mut placements = Vec::new();
+
+        for (shape, tile) in shapes.iter().enumerate() {
+            for cells in tile.orbit() {
+                for placement in self.translations(&cells) {
+                    placements.push((shape, placement));
+                }
+            }
+        }
+
+        placements
+    }
+
+    /// Returns the exact cover problem of tiling this board by the given tiles,
+    /// along with the placements making it up
+    fn exact_cover(&self, shapes: &[CubeTile]) -> (ExactCover, Vec<Vec<Position3>>) {
+        let positions = self.positions();
+        let placements: Vec<_> = self
+            .placements(shapes)
+            .into_iter()
+            .map(|(_, placement)| placement)
+            .collect();
+
+        (
+            ExactCover::from_positions(&positions, &placements),
+            placements,
+        )
+    }
+
+    /// Counts the tilings of this board by the given tiles, which may be used any number of
+    /// times and may be rotated and reflected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::cube::{CubeBoard, CubeTile};
+    ///
+    /// // domino tilings of a 2x2x2 cube
+    /// assert_eq!(CubeBoard::cuboid(2, 2, 2).count_tilings(&[CubeTile::bar(2)]), 9u32.into());
+    /// ```
+    pub fn count_tilings(&self, shapes: &[CubeTile]) -> BigUint {
+        self.exact_cover(shapes).0.count()
+    }
+
+    /// Finds a tiling of this board by the given tiles (as for `count_tilings`), returned as
+    /// the `(layer, row, col)` positions covered by each tile, or returns None if the board
+    /// can't be tiled
+    pub fn find_tiling(&self, shapes: &[CubeTile]) -> Option<Vec<Vec<Position3>>> {
+        let (problem, placements) = self.exact_cover(shapes);
+
+        problem.find().map(|solution| {
+            solution
+                .into_iter()
+                .map(|i| placements[i].clone())
+                .collect()
+        })
+    }
+
+    /// Returns the exact cover problem of filling this board with the given puzzle pieces,
+    /// along with the placements making it up.  Each piece is covered by an extra cell,
+    /// after the cells of the board, so that it's used exactly once.
+    fn puzzle(&self, pieces: &[CubeTile]) -> (ExactCover, Vec<Vec<Position3>>) {
+        let positions = self.positions();
+        let mut placements = Vec::new();
+        let mut cells = Vec::new();
+
+        for (piece, tile) in pieces.iter().enumerate() {
+            for orientation in tile.rotations() {
+                for placement in self.translations(&orientation) {
+                    let mut covered: Vec<usize> = placement
+                        .iter()
+                        .map(|p| positions.binary_search(p).unwrap())
+                        .collect();
+                    covered.push(positions.len() + piece);
+
+                    cells.push(covered);
+                    placements.push(placement);
+                }
+            }
+        }
+
+        (
+            ExactCover::new(positions.len() + pieces.len(), cells),
+            placements,
+        )
+    }
+
+    /// Counts the ways of filling this board with the given puzzle pieces, where each piece is
+    /// used exactly once and may be rotated, but not reflected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::cube::{CubeBoard, CubeTile};
+    ///
+    /// // the Soma cube has 240 solutions, up to the 48 symmetries of the cube
+    /// let solutions = CubeBoard::cuboid(3, 3, 3).count_puzzle_solutions(&CubeTile::soma());
+    /// assert_eq!(solutions, (240u32 * 48).into());
+    /// ```
+    pub fn count_puzzle_solutions(&self, pieces: &[CubeTile]) -> BigUint {
+        self.puzzle(pieces).0.count()
+    }
+
+    /// Finds a way of filling this board with the given puzzle pieces (as for
+    /// `count_puzzle_solutions`), returned as the `(layer, row, col)` positions covered by
+    /// each piece, in the same order as `pieces`.  Returns None if there isn't one.
+    pub fn find_puzzle_solution(&self, pieces: &[CubeTile]) -> Option<Vec<Vec<Position3>>> {
+        let (problem, placements) = self.puzzle(pieces);
+
+        problem.find().map(|mut solution| {
+            // the last cell of each placement is the extra cell for its piece
+            solution.sort_by_key(|i| problem.placement(*i).last().copied());
+            solution
+                .into_iter()
+                .map(|i| placements[i].clone())
+                .collect()
+        })
+    }
+}
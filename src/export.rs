@@ -0,0 +1,146 @@
+use crate::graph::BoardGraph;
+use std::fmt::Write;
+
+/// How to label the nodes of an exported graph
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NodeLabel {
+    /// An ASCII picture of the board, with `x` for marked positions and `*` for unmarked ones
+    Board,
+
+    /// The number of tiles placed down to get to the board from the initial board
+    Depth,
+}
+
+/// Returns the label of each node in our graph
+fn labels(graph: &BoardGraph, label: NodeLabel) -> Vec<String> {
+    match label {
+        NodeLabel::Board => (0..graph.len())
+            .map(|i| format!("{:?}", graph.get_node(i).unwrap()))
+            .collect(),
+        NodeLabel::Depth => graph
+            .depths()
+            .into_iter()
+            .map(|depth| depth.map_or_else(String::new, |d| d.to_string()))
+            .collect(),
+    }
+}
+
+/// Returns the edges of our graph, sorted so that the output is deterministic
+fn sorted_edges(graph: &BoardGraph, s: usize) -> Vec<usize> {
+    let mut edges: Vec<usize> = graph.get_edges(s).into_iter().flatten().copied().collect();
+    edges.sort_unstable();
+    edges
+}
+
+/// Exports a graph in the Graphviz DOT format.  Complete nodes are filled in.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::export::{to_dot, NodeLabel};
+/// use dcc_tiler::graph::BoardGraph;
+///
+/// let board = RectangularBoard::new(2, 1);
+/// let mut graph = BoardGraph::new();
+/// graph.add_node(board.clone());
+/// graph.add_node(board.place_tile_at(&[(0, 0), (0, 1)]).unwrap());
+/// graph.add_edge(0, 1);
+/// graph.mark_node_as_complete(1);
+///
+/// let dot = to_dot(&graph, NodeLabel::Depth);
+/// assert!(dot.contains("0 -> 1;"));
+/// assert!(dot.contains("1 [label=\"1\", style=filled"));
+/// ```
+pub fn to_dot(graph: &BoardGraph, label: NodeLabel) -> String {
+    let mut dot = String::new();
+
+    writeln!(dot, "digraph tilings {{").unwrap();
+    writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+    for (i, node_label) in labels(graph, label).into_iter().enumerate() {
+        // left-justify each line of the label
+        let node_label = node_label.replace('"', "\\\"").replace('\n', "\\l");
+
+        if graph.is_complete(i) {
+            writeln!(
+                dot,
+                "    {} [label=\"{}\", style=filled, fillcolor=\"#f5e663\"];",
+                i, node_label
+            )
+            .unwrap();
+        } else {
+            writeln!(dot, "    {} [label=\"{}\"];", i, node_label).unwrap();
+        }
+    }
+
+    for s in 0..graph.len() {
+        for t in sorted_edges(graph, s) {
+            writeln!(dot, "    {} -> {};", s, t).unwrap();
+        }
+    }
+
+    writeln!(dot, "}}").unwrap();
+
+    dot
+}
+
+/// Exports a graph in the GraphML format.  Each node has a `label` attribute, as well
+/// as a boolean `complete` attribute indicating whether it is a complete tiling.
+pub fn to_graphml(graph: &BoardGraph, label: NodeLabel) -> String {
+    let mut graphml = String::new();
+
+    writeln!(graphml, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+    writeln!(
+        graphml,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )
+    .unwrap();
+    writeln!(
+        graphml,
+        "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>"
+    )
+    .unwrap();
+    writeln!(
+        graphml,
+        "  <key id=\"complete\" for=\"node\" attr.name=\"complete\" attr.type=\"boolean\">"
+    )
+    .unwrap();
+    writeln!(graphml, "    <default>false</default>").unwrap();
+    writeln!(graphml, "  </key>").unwrap();
+    writeln!(graphml, "  <graph id=\"tilings\" edgedefault=\"directed\">").unwrap();
+
+    for (i, node_label) in labels(graph, label).into_iter().enumerate() {
+        writeln!(graphml, "    <node id=\"n{}\">", i).unwrap();
+        writeln!(
+            graphml,
+            "      <data key=\"label\">{}</data>",
+            escape_xml(&node_label)
+        )
+        .unwrap();
+
+        if graph.is_complete(i) {
+            writeln!(graphml, "      <data key=\"complete\">true</data>").unwrap();
+        }
+
+        writeln!(graphml, "    </node>").unwrap();
+    }
+
+    for s in 0..graph.len() {
+        for t in sorted_edges(graph, s) {
+            writeln!(graphml, "    <edge source=\"n{}\" target=\"n{}\"/>", s, t).unwrap();
+        }
+    }
+
+    writeln!(graphml, "  </graph>").unwrap();
+    writeln!(graphml, "</graphml>").unwrap();
+
+    graphml
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,182 @@
+use crate::tile::TileCollection;
+use crate::tiling::Tiling;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// The flip graph of a collection of tilings: two tilings are adjacent if one can be obtained
+/// from the other by a local move, i.e. by re-tiling the tiles lying inside some window of a
+/// fixed size.  For domino tilings with a 2x2 window, the moves are the usual domino flips.
+#[derive(Clone, Debug)]
+pub struct FlipGraph {
+    pub tilings: Vec<Tiling>,
+
+    // The neighbours of each tiling, as indices into `tilings`
+    neighbours: Vec<HashSet<usize>>,
+}
+
+impl FlipGraph {
+    /// Computes the flip graph of the given tilings, using windows with `(rows, cols)` given by
+    /// `window`.  Moves which lead to tilings which aren't in the given list are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::flip::FlipGraph;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiling::Tiling;
+    ///
+    /// // the three domino tilings of a 2x3 board
+    /// let board = RectangularBoard::new(3, 2);
+    /// let tiling = |tiles: &[&[(usize, usize)]]| {
+    ///     let mut boards = vec![board.clone()];
+    ///     for tile in tiles {
+    ///         boards.push(boards.last().unwrap().place_tile_at(tile).unwrap());
+    ///     }
+    ///     Tiling::from_boards(&boards)
+    /// };
+    /// let tilings = vec![
+    ///     tiling(&[&[(0, 0), (1, 0)], &[(0, 1), (1, 1)], &[(0, 2), (1, 2)]]),
+    ///     tiling(&[&[(0, 0), (0, 1)], &[(1, 0), (1, 1)], &[(0, 2), (1, 2)]]),
+    ///     tiling(&[&[(0, 0), (1, 0)], &[(0, 1), (0, 2)], &[(1, 1), (1, 2)]]),
+    /// ];
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    /// let flip_graph = FlipGraph::new(tilings, &tiles, (2, 2));
+    ///
+    /// assert!(flip_graph.is_connected());
+    /// assert_eq!(flip_graph.num_edges(), 2);
+    /// assert_eq!(flip_graph.diameter(), Some(2));
+    /// assert_eq!(flip_graph.degree_distribution()[&1], 2);
+    /// ```
+    pub fn new(tilings: Vec<Tiling>, tiles: &TileCollection, window: (usize, usize)) -> Self {
+        let index: HashMap<&Tiling, usize> =
+            tilings.iter().enumerate().map(|(i, t)| (t, i)).collect();
+
+        let mut neighbours = vec![HashSet::new(); tilings.len()];
+
+        for (i, tiling) in tilings.iter().enumerate() {
+            // every window which fits on the board (or a single window, if none do)
+            let rows = tiling.height.saturating_sub(window.0) + 1;
+            let cols = tiling.width.saturating_sub(window.1) + 1;
+
+            for top in 0..rows {
+                for left in 0..cols {
+                    for retiling in tiling.window_retilings(tiles, (top, left), window) {
+                        let mut neighbour = tiling.clone();
+                        neighbour.retile(&retiling);
+
+                        if neighbour == *tiling {
+                            continue;
+                        }
+
+                        if let Some(j) = index.get(&neighbour) {
+                            neighbours[i].insert(*j);
+                            neighbours[*j].insert(i);
+                        }
+                    }
+                }
+            }
+        }
+
+        FlipGraph {
+            tilings,
+            neighbours,
+        }
+    }
+
+    /// Returns the number of tilings in the graph
+    pub fn len(&self) -> usize {
+        self.tilings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tilings.is_empty()
+    }
+
+    /// Returns the number of (undirected) edges in the graph
+    pub fn num_edges(&self) -> usize {
+        self.neighbours.iter().map(|n| n.len()).sum::<usize>() / 2
+    }
+
+    /// Returns the indices of the tilings adjacent to the given tiling
+    pub fn neighbours(&self, i: usize) -> &HashSet<usize> {
+        &self.neighbours[i]
+    }
+
+    /// Returns the distance from the given tiling to every other tiling,
+    /// or None for the tilings which can't be reached from it
+    fn distances(&self, start: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        distances[start] = Some(0);
+        let mut queue = VecDeque::from(vec![start]);
+
+        while let Some(s) = queue.pop_front() {
+            let distance = distances[s].unwrap();
+
+            for t in self.neighbours[s].iter() {
+                if distances[*t].is_none() {
+                    distances[*t] = Some(distance + 1);
+                    queue.push_back(*t);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Returns the connected components of the graph, as lists of tiling indices
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.len()];
+        let mut components = Vec::new();
+
+        for start in 0..self.len() {
+            if seen[start] {
+                continue;
+            }
+
+            let component: Vec<usize> = self
+                .distances(start)
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| d.is_some())
+                .map(|(i, _)| i)
+                .collect();
+
+            for i in component.iter() {
+                seen[*i] = true;
+            }
+            components.push(component);
+        }
+
+        components
+    }
+
+    /// Returns whether every tiling can be reached from every other tiling by local moves
+    pub fn is_connected(&self) -> bool {
+        self.is_empty() || self.distances(0).iter().all(|d| d.is_some())
+    }
+
+    /// Returns the largest distance between two tilings, or None if the graph isn't connected
+    pub fn diameter(&self) -> Option<usize> {
+        let mut diameter = 0;
+
+        for start in 0..self.len() {
+            for distance in self.distances(start) {
+                diameter = diameter.max(distance?);
+            }
+        }
+
+        Some(diameter)
+    }
+
+    /// Returns the number of tilings with each degree
+    pub fn degree_distribution(&self) -> BTreeMap<usize, usize> {
+        let mut distribution = BTreeMap::new();
+
+        for neighbours in self.neighbours.iter() {
+            *distribution.entry(neighbours.len()).or_insert(0) += 1;
+        }
+
+        distribution
+    }
+}
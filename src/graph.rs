@@ -1,8 +1,44 @@
 use crate::board::RectangularBoard;
-use serde_derive::Serialize;
-use std::collections::{HashMap, HashSet};
+use num::{BigUint, One, Zero};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
 
-#[derive(Debug, Serialize, Default)]
+/// A graph whose nodes are boards, with an edge between two boards if we can get from one
+/// to the other by placing down a tile.
+///
+/// Graphs can be saved and loaded using serde.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::graph::BoardGraph;
+///
+/// let board = RectangularBoard::new(2, 1);
+/// let mut graph = BoardGraph::new();
+/// graph.add_node(board.clone());
+/// graph.add_node(board.place_tile_at(&[(0, 0), (0, 1)]).unwrap());
+/// graph.add_edge(0, 1);
+/// graph.mark_node_as_complete(1);
+///
+/// let json = serde_json::to_string(&graph).unwrap();
+/// let loaded: BoardGraph = serde_json::from_str(&json).unwrap();
+///
+/// assert_eq!(loaded.find_node(&board), Some(0));
+/// assert!(loaded.is_complete(1));
+/// assert_eq!(loaded.backward_counts()[0], 1u32.into());
+///
+/// // edges must join nodes in the graph
+/// let json = json.replace("[1]", "[5]");
+/// assert!(serde_json::from_str::<BoardGraph>(&json).is_err());
+/// ```
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(try_from = "SerializedBoardGraph")]
 pub struct BoardGraph {
     // The nodes in our graph are boards - we store there here inside a vec
     //// so that we dont have Rc<RefCell<..>> all over the place
@@ -11,6 +47,12 @@ pub struct BoardGraph {
     #[serde(skip_serializing)]
     nodes_arena_index: usize,
 
+    // An index from the hash of a board to the nodes with that hash, so that we can find
+    // nodes without scanning the whole arena.  We key on hashes rather than boards to
+    // avoid storing a second copy of every board.
+    #[serde(skip_serializing)]
+    nodes_index: HashMap<u64, Vec<usize>>,
+
     // An edge in our graph indicates that it is possible to get from one board state
     // to another by placing down a tile.
     edges: HashMap<usize, HashSet<usize>>,
@@ -22,11 +64,76 @@ pub struct BoardGraph {
     complete_indices: HashSet<usize>,
 }
 
+/// The data needed to reconstruct a `BoardGraph`, which is everything except our indices
+#[derive(Deserialize)]
+struct SerializedBoardGraph {
+    nodes_arena: Vec<RectangularBoard>,
+    edges: HashMap<usize, HashSet<usize>>,
+    rev_edges: HashMap<usize, HashSet<usize>>,
+    complete_indices: HashSet<usize>,
+}
+
+impl TryFrom<SerializedBoardGraph> for BoardGraph {
+    type Error = String;
+
+    fn try_from(graph: SerializedBoardGraph) -> Result<Self, Self::Error> {
+        let len = graph.nodes_arena.len();
+
+        if let Some(first) = graph.nodes_arena.first() {
+            if let Some(i) = graph
+                .nodes_arena
+                .iter()
+                .position(|b| (b.width, b.height) != (first.width, first.height))
+            {
+                return Err(format!(
+                    "node {} doesn't have dimensions {}x{}",
+                    i, first.width, first.height
+                ));
+            }
+        }
+
+        // every edge, in either direction, should join two nodes we have
+        for edges in [&graph.edges, &graph.rev_edges] {
+            for (s, targets) in edges.iter() {
+                if let Some(t) = std::iter::once(s).chain(targets).find(|i| **i >= len) {
+                    return Err(format!(
+                        "edge refers to node {}, but there are {} nodes",
+                        t, len
+                    ));
+                }
+            }
+        }
+
+        if let Some(i) = graph.complete_indices.iter().find(|i| **i >= len) {
+            return Err(format!(
+                "complete node {} doesn't exist, as there are {} nodes",
+                i, len
+            ));
+        }
+
+        let mut nodes_index: HashMap<u64, Vec<usize>> = HashMap::new();
+
+        for (i, node) in graph.nodes_arena.iter().enumerate() {
+            nodes_index.entry(hash_board(node)).or_default().push(i);
+        }
+
+        Ok(BoardGraph {
+            nodes_arena_index: len,
+            nodes_arena: graph.nodes_arena,
+            nodes_index,
+            edges: graph.edges,
+            rev_edges: graph.rev_edges,
+            complete_indices: graph.complete_indices,
+        })
+    }
+}
+
 impl BoardGraph {
     pub fn new() -> Self {
         BoardGraph {
             nodes_arena: Vec::new(),
             nodes_arena_index: 0,
+            nodes_index: HashMap::new(),
             edges: HashMap::new(),
             rev_edges: HashMap::new(),
             complete_indices: HashSet::new(),
@@ -41,17 +148,31 @@ impl BoardGraph {
         None
     }
 
+    /// Returns the number of nodes in our graph
+    pub fn len(&self) -> usize {
+        self.nodes_arena.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes_arena.is_empty()
+    }
+
     pub fn mark_node_as_complete(&mut self, i: usize) {
         self.complete_indices.insert(i);
     }
 
+    /// Returns whether the given node corresponds to a complete tiling
+    pub fn is_complete(&self, i: usize) -> bool {
+        self.complete_indices.contains(&i)
+    }
+
+    /// Returns the index of the node corresponding to the given board, if there is one
     pub fn find_node(&self, v: &RectangularBoard) -> Option<usize> {
-        for (i, node) in self.nodes_arena.iter().enumerate() {
-            if node == v {
-                return Some(i);
-            }
-        }
-        None
+        self.nodes_index
+            .get(&hash_board(v))?
+            .iter()
+            .copied()
+            .find(|i| self.nodes_arena[*i] == *v)
     }
 
     pub fn get_edges(&self, i: usize) -> Option<&HashSet<usize>> {
@@ -66,20 +187,400 @@ impl BoardGraph {
         self.nodes_arena.get(i)
     }
 
+    /// Adds a node corresponding to the given board, and returns its index.  If the board is
+    /// already in our graph then no new node is added, and the existing index is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let mut graph = BoardGraph::new();
+    /// let board = RectangularBoard::new(2, 2);
+    ///
+    /// assert_eq!(graph.add_node(board.clone()), 0);
+    /// assert_eq!(graph.add_node(board.place_tile_at(&[(0, 0)]).unwrap()), 1);
+    /// assert_eq!(graph.add_node(board.clone()), 0);
+    /// assert_eq!(graph.find_node(&board), Some(0));
+    /// assert_eq!(graph.len(), 2);
+    /// ```
     pub fn add_node(&mut self, v: RectangularBoard) -> usize {
+        if let Some(i) = self.find_node(&v) {
+            return i;
+        }
+
+        self.nodes_index
+            .entry(hash_board(&v))
+            .or_default()
+            .push(self.nodes_arena_index);
         self.nodes_arena.push(v);
 
         self.nodes_arena_index += 1;
         self.nodes_arena_index - 1
     }
 
+    /// Returns the indices of the nodes in our graph, ordered so that every
+    /// edge goes from an earlier node to a later node
+    pub fn topological_order(&self) -> Vec<usize> {
+        let mut in_degree: Vec<usize> = (0..self.len())
+            .map(|i| self.get_rev_edges(i).map_or(0, |e| e.len()))
+            .collect();
+
+        let mut order: Vec<usize> = (0..self.len()).filter(|i| in_degree[*i] == 0).collect();
+        let mut next = 0;
+
+        while next < order.len() {
+            if let Some(edges) = self.get_edges(order[next]) {
+                for t in edges {
+                    in_degree[*t] -= 1;
+                    if in_degree[*t] == 0 {
+                        order.push(*t);
+                    }
+                }
+            }
+            next += 1;
+        }
+
+        order
+    }
+
+    /// Returns, for each node, the number of paths from node 0 to that node.  This is
+    /// the number of ways of getting to the corresponding board by placing down tiles.
+    pub fn forward_counts(&self) -> Vec<BigUint> {
+        let mut counts = vec![BigUint::zero(); self.len()];
+
+        if self.is_empty() {
+            return counts;
+        }
+        counts[0] = BigUint::one();
+
+        for s in self.topological_order() {
+            if let Some(edges) = self.get_edges(s) {
+                let count = counts[s].clone();
+
+                for t in edges {
+                    counts[*t] += &count;
+                }
+            }
+        }
+
+        counts
+    }
+
+    /// Returns, for each node, the number of paths from that node to a complete node.
+    /// This is the number of ways of completing the corresponding board to a tiling.
+    pub fn backward_counts(&self) -> Vec<BigUint> {
+        let mut counts = vec![BigUint::zero(); self.len()];
+
+        for i in self.complete_indices.iter() {
+            counts[*i] = BigUint::one();
+        }
+
+        for t in self.topological_order().into_iter().rev() {
+            if let Some(rev_edges) = self.get_rev_edges(t) {
+                let count = counts[t].clone();
+
+                for s in rev_edges {
+                    counts[*s] += &count;
+                }
+            }
+        }
+
+        counts
+    }
+
     pub fn add_edge(&mut self, s: usize, t: usize) {
         assert!(s < self.nodes_arena_index && t < self.nodes_arena_index);
 
-        self.edges.entry(s).or_insert_with(HashSet::new).insert(t);
-        self.rev_edges
-            .entry(t)
-            .or_insert_with(HashSet::new)
-            .insert(s);
+        self.edges.entry(s).or_default().insert(t);
+        self.rev_edges.entry(t).or_default().insert(s);
+    }
+
+    /// Returns, for each node, whether it can be reached from one of the `start` nodes
+    /// by following the given edges
+    fn reachable(&self, start: Vec<usize>, edges: &HashMap<usize, HashSet<usize>>) -> Vec<bool> {
+        let mut seen = vec![false; self.len()];
+        let mut stack = start;
+
+        while let Some(s) = stack.pop() {
+            if !std::mem::replace(&mut seen[s], true) {
+                stack.extend(edges.get(&s).into_iter().flatten());
+            }
+        }
+
+        seen
+    }
+
+    /// Returns, for each node, the smallest number of tiles which need to be placed down to get
+    /// from the initial board to the corresponding board (or None if the board isn't reachable)
+    pub fn depths(&self) -> Vec<Option<usize>> {
+        let mut depths = vec![None; self.len()];
+
+        if self.is_empty() {
+            return depths;
+        }
+
+        depths[0] = Some(0);
+        let mut queue = VecDeque::from(vec![0]);
+
+        while let Some(s) = queue.pop_front() {
+            let depth = depths[s].unwrap();
+
+            for t in self.get_edges(s).into_iter().flatten() {
+                if depths[*t].is_none() {
+                    depths[*t] = Some(depth + 1);
+                    queue.push_back(*t);
+                }
+            }
+        }
+
+        depths
+    }
+
+    /// Computes statistics about the shape and size of our graph
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let board = RectangularBoard::new(3, 1);
+    /// let mut graph = BoardGraph::new();
+    /// graph.add_node(board.clone());
+    /// graph.add_node(board.place_tile_at(&[(0, 1), (0, 2)]).unwrap());
+    /// graph.add_node(board.place_tile_at(&[(0, 0)]).unwrap());
+    /// graph.add_node(board.place_tile_at(&[(0, 0), (0, 1), (0, 2)]).unwrap());
+    /// graph.add_edge(0, 1);
+    /// graph.add_edge(0, 2);
+    /// graph.add_edge(2, 3);
+    /// graph.mark_node_as_complete(3);
+    ///
+    /// let stats = graph.stats();
+    /// assert_eq!(stats.nodes_per_depth, vec![1, 2, 1]);
+    /// assert_eq!(stats.edges_per_depth, vec![2, 1, 0]);
+    /// assert_eq!(stats.dead_ends, 1);
+    /// assert_eq!(stats.max_branching, 2);
+    /// assert_eq!(stats.paths, 1u32.into());
+    /// ```
+    pub fn stats(&self) -> GraphStats {
+        let depths = self.depths();
+        let num_levels = depths.iter().flatten().max().map_or(0, |d| d + 1);
+
+        let mut nodes_per_depth = vec![0; num_levels];
+        let mut edges_per_depth = vec![0; num_levels];
+        let mut edges = 0;
+        let mut max_branching = 0;
+
+        for (i, depth) in depths.iter().enumerate() {
+            let degree = self.get_edges(i).map_or(0, |e| e.len());
+
+            if let Some(depth) = depth {
+                nodes_per_depth[*depth] += 1;
+                edges_per_depth[*depth] += degree;
+            }
+
+            edges += degree;
+            max_branching = max_branching.max(degree);
+        }
+
+        let completable = self.reachable(
+            self.complete_indices.iter().copied().collect(),
+            &self.rev_edges,
+        );
+
+        GraphStats {
+            nodes: self.len(),
+            edges,
+            nodes_per_depth,
+            edges_per_depth,
+            dead_ends: completable.iter().filter(|c| !**c).count(),
+            max_branching,
+            memory: self.memory_use(),
+            paths: if self.is_empty() {
+                BigUint::zero()
+            } else {
+                self.backward_counts().swap_remove(0)
+            },
+        }
+    }
+
+    /// Estimates the number of bytes of memory used by our graph.  This doesn't take into
+    /// account any unused capacity in our collections.
+    fn memory_use(&self) -> usize {
+        let board_size = self.get_node(0).map_or(0, |board| {
+            size_of::<RectangularBoard>()
+                + board.height
+                    * (2 * size_of::<Vec<usize>>() + board.width * (1 + size_of::<usize>()))
+        });
+
+        // hash tables store a control byte for each entry
+        let edge_map_size = |edges: &HashMap<usize, HashSet<usize>>| {
+            edges.len() * (1 + size_of::<(usize, HashSet<usize>)>())
+                + edges.values().map(|e| e.len()).sum::<usize>() * (1 + size_of::<usize>())
+        };
+
+        size_of::<BoardGraph>()
+            + self.len() * board_size
+            + self.nodes_index.len() * (1 + size_of::<(u64, Vec<usize>)>())
+            + self.len() * size_of::<usize>()
+            + edge_map_size(&self.edges)
+            + edge_map_size(&self.rev_edges)
+            + self.complete_indices.len() * (1 + size_of::<usize>())
+    }
+
+    /// Removes every node which doesn't lie on a path from node 0 to a complete node, i.e. every
+    /// board which can't be reached from the initial board, or can't be extended to a tiling.
+    /// Node 0 is always kept.  The remaining nodes keep their relative order, but are renumbered.
+    ///
+    /// Returns the number of nodes removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    ///
+    /// let board = RectangularBoard::new(3, 1);
+    /// let mut graph = BoardGraph::new();
+    /// graph.add_node(board.clone());
+    /// graph.add_node(board.place_tile_at(&[(0, 1), (0, 2)]).unwrap());
+    /// graph.add_node(board.place_tile_at(&[(0, 0)]).unwrap());
+    /// graph.add_node(board.place_tile_at(&[(0, 0), (0, 1), (0, 2)]).unwrap());
+    /// graph.add_edge(0, 1);
+    /// graph.add_edge(0, 2);
+    /// graph.add_edge(2, 3);
+    /// graph.mark_node_as_complete(3);
+    ///
+    /// // node 1 is a dead end
+    /// assert_eq!(graph.prune(), 1);
+    /// assert_eq!(graph.len(), 3);
+    /// assert!(graph.is_complete(2));
+    /// assert_eq!(graph.find_node(&board.place_tile_at(&[(0, 1), (0, 2)]).unwrap()), None);
+    /// ```
+    pub fn prune(&mut self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        // find the nodes we can reach from node 0, and the nodes from which we can reach a complete node
+        let forward = self.reachable(vec![0], &self.edges);
+        let backward = self.reachable(
+            self.complete_indices.iter().copied().collect(),
+            &self.rev_edges,
+        );
+
+        let keep: Vec<bool> = (0..self.len())
+            .map(|i| i == 0 || (forward[i] && backward[i]))
+            .collect();
+
+        let removed = keep.iter().filter(|k| !**k).count();
+
+        if removed == 0 {
+            return 0;
+        }
+
+        // rebuild the graph out of the nodes we're keeping
+        let old = std::mem::take(self);
+        let mut new_index = vec![None; old.len()];
+
+        for (i, node) in old.nodes_arena.into_iter().enumerate() {
+            if keep[i] {
+                new_index[i] = Some(self.add_node(node));
+            }
+        }
+
+        for (s, edges) in old.edges.iter() {
+            for t in edges {
+                if let (Some(s), Some(t)) = (new_index[*s], new_index[*t]) {
+                    self.add_edge(s, t);
+                }
+            }
+        }
+
+        for i in old.complete_indices.iter() {
+            if let Some(i) = new_index[*i] {
+                self.mark_node_as_complete(i);
+            }
+        }
+
+        removed
+    }
+}
+
+fn hash_board(v: &RectangularBoard) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    v.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Statistics about a `BoardGraph`, as computed by `BoardGraph::stats`
+#[derive(Clone, Debug)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+
+    /// The number of nodes at each depth, where the depth of a node is the smallest
+    /// number of tiles placed down to get to it from the initial board
+    pub nodes_per_depth: Vec<usize>,
+
+    /// The number of edges leaving the nodes at each depth
+    pub edges_per_depth: Vec<usize>,
+
+    /// The number of nodes which can't be extended to a complete tiling
+    pub dead_ends: usize,
+
+    /// The largest number of edges leaving a single node
+    pub max_branching: usize,
+
+    /// An estimate of the number of bytes of memory used by the graph
+    pub memory: usize,
+
+    /// The number of paths from the initial board to a complete tiling
+    pub paths: BigUint,
+}
+
+impl GraphStats {
+    /// Returns the proportion of nodes which are dead ends
+    pub fn dead_end_fraction(&self) -> f64 {
+        if self.nodes == 0 {
+            0.0
+        } else {
+            self.dead_ends as f64 / self.nodes as f64
+        }
+    }
+}
+
+impl fmt::Display for GraphStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "nodes: {}", self.nodes)?;
+        writeln!(f, "edges: {}", self.edges)?;
+        writeln!(
+            f,
+            "dead ends: {} ({:.1}%)",
+            self.dead_ends,
+            100.0 * self.dead_end_fraction()
+        )?;
+        writeln!(f, "maximum branching factor: {}", self.max_branching)?;
+        writeln!(
+            f,
+            "approximate memory use: {:.1} KiB",
+            self.memory as f64 / 1024.0
+        )?;
+        writeln!(f, "paths to a complete tiling: {}", self.paths)?;
+        writeln!(f)?;
+        writeln!(f, "depth,nodes,edges")?;
+
+        for (depth, (nodes, edges)) in self
+            .nodes_per_depth
+            .iter()
+            .zip(self.edges_per_depth.iter())
+            .enumerate()
+        {
+            writeln!(f, "{},{},{}", depth, nodes, edges)?;
+        }
+
+        Ok(())
     }
 }
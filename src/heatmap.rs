@@ -0,0 +1,141 @@
+use crate::graph::BoardGraph;
+use crate::tile::{normalize_positions, TileCollection};
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use num::{BigUint, ToPrimitive, Zero};
+use std::collections::HashMap;
+
+/// For each position on a board and each tile orientation, the number of tilings of the board
+/// in which that position is covered by a tile with that orientation.
+///
+/// Dividing by the total number of tilings gives the probability that a uniformly random
+/// tiling covers the position by a tile with the orientation.
+#[derive(Clone, Debug)]
+pub struct Heatmap {
+    pub width: usize,
+    pub height: usize,
+
+    /// The positions which were already marked on the initial board
+    pub marked: Vec<Vec<bool>>,
+
+    /// The blocks making up each tile orientation, as returned by `TileCollection::orientations`
+    pub orientations: Vec<Vec<(isize, isize)>>,
+
+    /// The tiling counts, indexed as `counts[orientation][row][col]`
+    pub counts: Vec<Vec<Vec<BigUint>>>,
+
+    /// The total number of tilings
+    pub total: BigUint,
+}
+
+impl Heatmap {
+    /// Computes a heatmap from a tiling graph.
+    ///
+    /// Each edge `s -> t` of the graph corresponds to placing down a tile, which appears in
+    /// `forward[s] * backward[t]` tilings, where `forward` and `backward` are the path counts
+    /// from the initial board and to the complete board respectively.  Returns None if the
+    /// graph is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::graph::BoardGraph;
+    /// use dcc_tiler::heatmap::Heatmap;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// // the only tiling of a 1x2 board by dominoes
+    /// let board = RectangularBoard::new(2, 1);
+    /// let mut graph = BoardGraph::new();
+    /// graph.add_node(board.clone());
+    /// graph.add_node(board.place_tile_at(&[(0, 0), (0, 1)]).unwrap());
+    /// graph.add_edge(0, 1);
+    /// graph.mark_node_as_complete(1);
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    /// let heatmap = Heatmap::from_graph(&graph, &tiles).unwrap();
+    /// let horizontal = tiles.orientation_of(&[(0, 0), (0, 1)]).unwrap();
+    ///
+    /// assert_eq!(heatmap.probability(horizontal, (0, 1)), 1.0);
+    /// assert_eq!(heatmap.probability(1 - horizontal, (0, 1)), 0.0);
+    /// ```
+    pub fn from_graph(graph: &BoardGraph, tiles: &TileCollection) -> Option<Self> {
+        let initial_board = graph.get_node(0)?;
+        let (width, height) = (initial_board.width, initial_board.height);
+
+        let orientations = tiles.orientations();
+        let orientation_map: HashMap<_, _> = orientations
+            .iter()
+            .enumerate()
+            .map(|(i, o)| (o.clone(), i))
+            .collect();
+
+        let forward = graph.forward_counts();
+        let backward = graph.backward_counts();
+
+        let mut counts = vec![vec![vec![BigUint::zero(); width]; height]; orientations.len()];
+
+        for (s, forward_count) in forward.iter().enumerate() {
+            if forward_count.is_zero() {
+                continue;
+            }
+
+            for t in graph.get_edges(s).into_iter().flatten() {
+                if backward[*t].is_zero() {
+                    continue;
+                }
+
+                let positions = graph
+                    .get_node(s)
+                    .unwrap()
+                    .changed_positions(graph.get_node(*t).unwrap());
+
+                if let Some(o) = orientation_map.get(&normalize_positions(&positions)) {
+                    let tilings = forward_count * &backward[*t];
+
+                    for (row, col) in positions {
+                        counts[*o][row][col] += &tilings;
+                    }
+                }
+            }
+        }
+
+        Some(Heatmap {
+            width,
+            height,
+            marked: initial_board.board.clone(),
+            orientations,
+            counts,
+            total: backward[0].clone(),
+        })
+    }
+
+    /// Records that every tiling contains a tile covering the given `(row, col)` positions,
+    /// which are marked on the initial board.  This is used for tiles which were placed in advance.
+    pub fn add_fixed_tile(&mut self, positions: &[(usize, usize)]) {
+        let cells = normalize_positions(positions);
+
+        if let Some(o) = self.orientations.iter().position(|o| *o == cells) {
+            for (row, col) in positions {
+                self.counts[o][*row][*col] += &self.total;
+                self.marked[*row][*col] = false;
+            }
+        }
+    }
+
+    /// Returns the probability that a uniformly random tiling covers the given
+    /// `(row, col)` position by a tile with the given orientation
+    pub fn probability(&self, orientation: usize, position: (usize, usize)) -> f64 {
+        if self.total.is_zero() {
+            return 0.0;
+        }
+
+        let (row, col) = position;
+        BigRational::new(
+            BigInt::from(self.counts[orientation][row][col].clone()),
+            BigInt::from(self.total.clone()),
+        )
+        .to_f64()
+        .unwrap_or(0.0)
+    }
+}
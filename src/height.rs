@@ -0,0 +1,185 @@
+use crate::tiling::Tiling;
+use std::collections::VecDeque;
+
+/// The Thurston height function of a domino tiling.
+///
+/// Heights are defined on the vertices of the grid, so that `(row, col)` is the top left corner
+/// of the position `(row, col)`.  Colour the positions like a chessboard, with `(0, 0)` black.
+/// Walking along an edge between two vertices, the height goes up by 1 if the position on our
+/// left is black and down by 1 if it is white, unless we cross a domino, in which case it goes
+/// down by 3 or up by 3 respectively.  The first vertex of the board (in row-major order) has height 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeightFunction {
+    /// The width of the board, so that there are `width + 1` columns of vertices
+    pub width: usize,
+
+    /// The height of the board, so that there are `height + 1` rows of vertices
+    pub height: usize,
+
+    // The height at each vertex, or None for vertices which aren't on the board
+    heights: Vec<Vec<Option<i64>>>,
+}
+
+/// Returns +1 if the position is black, and -1 if it is white
+fn sign(row: isize, col: isize) -> i64 {
+    if (row + col).rem_euclid(2) == 0 {
+        1
+    } else {
+        -1
+    }
+}
+
+impl HeightFunction {
+    /// Computes the height function of a tiling.  Returns None if the tiling isn't a domino tiling,
+    /// or if the height function isn't well-defined (which can happen when the board has holes).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::height::HeightFunction;
+    /// use dcc_tiler::tiling::Tiling;
+    ///
+    /// // two horizontal dominoes
+    /// let board = RectangularBoard::new(2, 2);
+    /// let first = board.place_tile_at(&[(0, 0), (0, 1)]).unwrap();
+    /// let second = first.place_tile_at(&[(1, 0), (1, 1)]).unwrap();
+    /// let tiling = Tiling::from_boards(&[board, first, second]);
+    ///
+    /// let heights = HeightFunction::from_tiling(&tiling).unwrap();
+    /// assert_eq!(heights.get((0, 0)), Some(0));
+    /// assert_eq!(heights.get((1, 1)), Some(2));
+    /// assert_eq!(heights.to_csv(), "0,-1,0\n1,2,1\n0,-1,0\n");
+    /// ```
+    pub fn from_tiling(tiling: &Tiling) -> Option<Self> {
+        let is_domino = |tile: &Vec<(usize, usize)>| {
+            tile.len() == 2 && tile[0].0.abs_diff(tile[1].0) + tile[0].1.abs_diff(tile[1].1) == 1
+        };
+
+        if !tiling.tiles().iter().all(is_domino) {
+            return None;
+        }
+
+        let (width, height) = (tiling.width, tiling.height);
+
+        let on_board = |row: isize, col: isize| {
+            row >= 0
+                && col >= 0
+                && (row as usize) < height
+                && (col as usize) < width
+                && tiling.is_covered((row as usize, col as usize))
+        };
+
+        // The change in height going along the edge between two positions, where `left`
+        // is on our left and `right` is on our right.  Returns None if neither position is
+        // on the board.
+        let step = |left: (isize, isize), right: (isize, isize)| {
+            if !on_board(left.0, left.1) && !on_board(right.0, right.1) {
+                return None;
+            }
+
+            let crosses_domino = on_board(left.0, left.1)
+                && on_board(right.0, right.1)
+                && tiling.same_tile(
+                    (left.0 as usize, left.1 as usize),
+                    (right.0 as usize, right.1 as usize),
+                );
+
+            let change = sign(left.0, left.1);
+            Some(if crosses_domino { -3 * change } else { change })
+        };
+
+        let mut heights = vec![vec![None; width + 1]; height + 1];
+
+        // the vertices touching the board
+        let start = (0..=height)
+            .flat_map(|i| (0..=width).map(move |j| (i, j)))
+            .find(|(i, j)| {
+                let (i, j) = (*i as isize, *j as isize);
+                on_board(i - 1, j - 1) || on_board(i - 1, j) || on_board(i, j - 1) || on_board(i, j)
+            });
+
+        let start = match start {
+            Some(start) => start,
+            None => {
+                return Some(HeightFunction {
+                    width,
+                    height,
+                    heights,
+                })
+            }
+        };
+
+        heights[start.0][start.1] = Some(0);
+        let mut queue = VecDeque::from(vec![start]);
+
+        while let Some((i, j)) = queue.pop_front() {
+            let h = heights[i][j].unwrap();
+            let (r, c) = (i as isize, j as isize);
+
+            // walking right, down, left and up respectively
+            let neighbours = [
+                ((r, c + 1), step((r - 1, c), (r, c))),
+                ((r + 1, c), step((r, c), (r, c - 1))),
+                ((r, c - 1), step((r, c - 1), (r - 1, c - 1))),
+                ((r - 1, c), step((r - 1, c - 1), (r - 1, c))),
+            ];
+
+            for ((ni, nj), change) in neighbours.iter() {
+                let change = match change {
+                    Some(change) => *change,
+                    None => continue,
+                };
+                let (ni, nj) = (*ni as usize, *nj as usize);
+
+                match heights[ni][nj] {
+                    Some(existing) if existing != h + change => return None,
+                    Some(_) => {}
+                    None => {
+                        heights[ni][nj] = Some(h + change);
+                        queue.push_back((ni, nj));
+                    }
+                }
+            }
+        }
+
+        Some(HeightFunction {
+            width,
+            height,
+            heights,
+        })
+    }
+
+    /// Returns the height at the given `(row, col)` vertex, or None if the vertex isn't on the board
+    pub fn get(&self, vertex: (usize, usize)) -> Option<i64> {
+        self.heights.get(vertex.0)?.get(vertex.1).copied().flatten()
+    }
+
+    /// Returns the smallest and largest heights, or None if the board is empty
+    pub fn range(&self) -> Option<(i64, i64)> {
+        let heights = self.heights.iter().flatten().flatten();
+
+        Some((*heights.clone().min()?, *heights.max()?))
+    }
+
+    /// Returns the heights as CSV, with a row for each row of vertices.  Vertices which aren't
+    /// on the board are left empty.
+    pub fn to_csv(&self) -> String {
+        self.heights
+            .iter()
+            .map(|row| {
+                let row: Vec<String> = row
+                    .iter()
+                    .map(|h| h.map_or_else(String::new, |h| h.to_string()))
+                    .collect();
+                row.join(",") + "\n"
+            })
+            .collect()
+    }
+
+    /// Returns the heights as a JSON array of rows of vertices.  Vertices which aren't on
+    /// the board are null.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.heights).unwrap()
+    }
+}
@@ -0,0 +1,360 @@
+//! Tilings of regions of the hexagonal grid by polyhexes.
+//!
+//! Hexagonal cells are indexed by "axial" `(row, col)` coordinates: rows increase downwards as
+//! usual, but each row is shifted half a cell to the right of the one above it.  So a `width` x
+//! `height` rectangle of coordinates is a parallelogram of hexagons, and each cell has six
+//! neighbours, in the directions given by `HexDirection`.
+
+use crate::cover::ExactCover;
+use num::BigUint;
+
+/// The six directions from a hexagonal cell to its neighbours
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+impl HexDirection {
+    /// Returns all six directions, anticlockwise starting from `East`
+    pub fn all() -> [HexDirection; 6] {
+        [
+            HexDirection::East,
+            HexDirection::NorthEast,
+            HexDirection::NorthWest,
+            HexDirection::West,
+            HexDirection::SouthWest,
+            HexDirection::SouthEast,
+        ]
+    }
+
+    /// Returns the position of this direction in `HexDirection::all`
+    fn index(self) -> usize {
+        HexDirection::all().iter().position(|d| *d == self).unwrap()
+    }
+
+    /// Returns the `(row, col)` offset obtained by moving one step in this direction
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            HexDirection::East => (0, 1),
+            HexDirection::NorthEast => (-1, 1),
+            HexDirection::NorthWest => (-1, 0),
+            HexDirection::West => (0, -1),
+            HexDirection::SouthWest => (1, -1),
+            HexDirection::SouthEast => (1, 0),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        HexDirection::all()[(self.index() + 3) % 6]
+    }
+
+    /// Returns this direction rotated by 60 degrees anticlockwise
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::HexDirection;
+    ///
+    /// assert_eq!(HexDirection::East.rotate(), HexDirection::NorthEast);
+    /// assert_eq!(HexDirection::SouthEast.rotate(), HexDirection::East);
+    /// ```
+    pub fn rotate(self) -> Self {
+        HexDirection::all()[(self.index() + 1) % 6]
+    }
+
+    /// Returns this direction reflected about the east-west axis
+    pub fn reflect(self) -> Self {
+        HexDirection::all()[(6 - self.index()) % 6]
+    }
+}
+
+/// A polyhex, described by a walk through its cells (as for `Tile`)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HexTile {
+    pub directions: Vec<HexDirection>,
+}
+
+impl HexTile {
+    pub fn new(directions: Vec<HexDirection>) -> Self {
+        HexTile { directions }
+    }
+
+    /// Returns a tile consisting of a single hexagon
+    pub fn single() -> Self {
+        HexTile::new(Vec::new())
+    }
+
+    /// Returns a straight tile consisting of `length` hexagons
+    ///
+    /// # Panics
+    ///
+    /// Will panic if length = 0
+    pub fn bar(length: usize) -> Self {
+        assert!(length > 0);
+
+        HexTile::new(vec![HexDirection::East; length - 1])
+    }
+
+    /// Returns a tile consisting of three hexagons around a common corner
+    pub fn triangle() -> Self {
+        HexTile::new(vec![HexDirection::East, HexDirection::NorthWest])
+    }
+
+    /// Returns a copy of this tile rotated by 60 degrees anticlockwise
+    pub fn rotate(&self) -> HexTile {
+        HexTile::new(self.directions.iter().map(|d| d.rotate()).collect())
+    }
+
+    /// Returns a copy of this tile reflected about the east-west axis
+    pub fn reflect(&self) -> HexTile {
+        HexTile::new(self.directions.iter().map(|d| d.reflect()).collect())
+    }
+
+    /// Returns the `(row, col)` offsets of the cells making up this tile, translated
+    /// so that the smallest row and column are both zero.  The result is sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::HexTile;
+    ///
+    /// assert_eq!(HexTile::triangle().cells(), vec![(0, 1), (1, 0), (1, 1)]);
+    /// ```
+    pub fn cells(&self) -> Vec<(isize, isize)> {
+        let mut current = (0, 0);
+        let mut cells = vec![current];
+
+        for direction in &self.directions {
+            let (dr, dc) = direction.offset();
+            current = (current.0 + dr, current.1 + dc);
+            cells.push(current);
+        }
+
+        let min_row = cells.iter().map(|c| c.0).min().unwrap();
+        let min_col = cells.iter().map(|c| c.1).min().unwrap();
+
+        let mut cells: Vec<_> = cells
+            .into_iter()
+            .map(|(r, c)| (r - min_row, c - min_col))
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+
+    /// Returns the distinct orientations of this tile under the twelve rotations and
+    /// reflections of the hexagonal grid (the dihedral group of order 12)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::{HexDirection, HexTile};
+    ///
+    /// assert_eq!(HexTile::bar(3).orbit().len(), 3);
+    /// assert_eq!(HexTile::triangle().orbit().len(), 2);
+    ///
+    /// // the bent trihex
+    /// let bent = HexTile::new(vec![HexDirection::East, HexDirection::NorthEast]);
+    /// assert_eq!(bent.orbit().len(), 6);
+    /// ```
+    pub fn orbit(&self) -> Vec<HexTile> {
+        let mut orbit: Vec<HexTile> = Vec::new();
+
+        for mut tile in [self.clone(), self.reflect()] {
+            for _ in 0..6 {
+                if !orbit.iter().any(|t| t.cells() == tile.cells()) {
+                    orbit.push(tile.clone());
+                }
+                tile = tile.rotate();
+            }
+        }
+
+        orbit
+    }
+}
+
+/// A region of the hexagonal grid, given by the cells of a `width` x `height` parallelogram
+/// which aren't marked
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HexBoard {
+    pub width: usize,
+    pub height: usize,
+    pub board: Vec<Vec<bool>>,
+}
+
+impl HexBoard {
+    /// Returns a parallelogram of hexagons, `width` wide and `height` tall
+    pub fn parallelogram(width: usize, height: usize) -> Self {
+        HexBoard::from_marks(vec![vec![false; width]; height])
+    }
+
+    /// Returns a hexagon of hexagons, with `side` hexagons along each side
+    ///
+    /// # Panics
+    ///
+    /// Will panic if side = 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::HexBoard;
+    ///
+    /// assert_eq!(HexBoard::hexagon(2).positions().len(), 7);
+    /// assert_eq!(HexBoard::hexagon(3).positions().len(), 19);
+    /// ```
+    pub fn hexagon(side: usize) -> Self {
+        assert!(side > 0);
+
+        let size = 2 * side - 1;
+
+        // the corners of the parallelogram cut off to make a hexagon
+        HexBoard::from_marks(
+            (0..size)
+                .map(|row| {
+                    (0..size)
+                        .map(|col| row + col < side - 1 || row + col > 3 * (side - 1))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Creates a board from the marked positions of a parallelogram, indexed by `(row, col)`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the board is empty.
+    pub fn from_marks(board: Vec<Vec<bool>>) -> Self {
+        HexBoard {
+            width: board[0].len(),
+            height: board.len(),
+            board,
+        }
+    }
+
+    /// Returns the `(row, col)` positions which aren't marked, in row-major order
+    pub fn positions(&self) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .filter(|(row, col)| !self.board[*row][*col])
+            .collect()
+    }
+
+    /// Returns the position next to the given `(row, col)` position in the given direction,
+    /// or None if that isn't an unmarked position of the board
+    pub fn neighbour(
+        &self,
+        (row, col): (usize, usize),
+        direction: HexDirection,
+    ) -> Option<(usize, usize)> {
+        let (dr, dc) = direction.offset();
+        let (row, col) = (row as isize + dr, col as isize + dc);
+
+        if row < 0 || col < 0 || row as usize >= self.height || col as usize >= self.width {
+            return None;
+        }
+
+        let position = (row as usize, col as usize);
+        Some(position).filter(|(row, col)| !self.board[*row][*col])
+    }
+
+    /// Returns every way of placing down one of the given tiles (in any orientation) on the
+    /// unmarked positions of the board, along with the index of its shape in `shapes`
+    pub fn placements(&self, shapes: &[HexTile]) -> Vec<(usize, Vec<(usize, usize)>)> {
+        let mut placements = Vec::new();
+
+        for (shape, tile) in shapes.iter().enumerate() {
+            for orientation in tile.orbit() {
+                let cells = orientation.cells();
+                let first = cells[0];
+
+                // line up the first cell of the tile with each position in turn
+                for (row, col) in self.positions() {
+                    let placement: Option<Vec<(usize, usize)>> = cells
+                        .iter()
+                        .map(|(r, c)| {
+                            let (r, c) = (row as isize + r - first.0, col as isize + c - first.1);
+
+                            if r < 0 || c < 0 {
+                                return None;
+                            }
+
+                            let (r, c) = (r as usize, c as usize);
+                            Some((r, c))
+                                .filter(|_| r < self.height && c < self.width && !self.board[r][c])
+                        })
+                        .collect();
+
+                    if let Some(placement) = placement {
+                        placements.push((shape, placement));
+                    }
+                }
+            }
+        }
+
+        placements
+    }
+
+    /// Returns the exact cover problem of tiling this board by the given tiles,
+    /// along with the placements making it up
+    fn exact_cover(&self, shapes: &[HexTile]) -> (ExactCover, Vec<Vec<(usize, usize)>>) {
+        let positions = self.positions();
+        let placements: Vec<_> = self
+            .placements(shapes)
+            .into_iter()
+            .map(|(_, placement)| placement)
+            .collect();
+
+        (
+            ExactCover::from_positions(&positions, &placements),
+            placements,
+        )
+    }
+
+    /// Counts the tilings of this board by the given tiles, which may be rotated and reflected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::{HexBoard, HexTile};
+    ///
+    /// let dihex = HexTile::bar(2);
+    /// assert_eq!(HexBoard::parallelogram(2, 2).count_tilings(&[dihex.clone()]), 2u32.into());
+    /// assert_eq!(HexBoard::hexagon(2).count_tilings(&[dihex]), 0u32.into());
+    ///
+    /// // the hexagon of side 2 without its centre is a ring of six hexagons
+    /// let mut ring = HexBoard::hexagon(2);
+    /// ring.board[1][1] = true;
+    /// assert_eq!(ring.count_tilings(&[HexTile::bar(2)]), 2u32.into());
+    /// ```
+    pub fn count_tilings(&self, shapes: &[HexTile]) -> BigUint {
+        self.exact_cover(shapes).0.count()
+    }
+
+    /// Finds a tiling of this board by the given tiles, returned as the `(row, col)` positions
+    /// covered by each tile, or returns None if the board can't be tiled
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::hex::{HexBoard, HexTile};
+    ///
+    /// let tiling = HexBoard::hexagon(2).find_tiling(&[HexTile::triangle(), HexTile::single()]);
+    /// assert!(tiling.is_some());
+    /// ```
+    pub fn find_tiling(&self, shapes: &[HexTile]) -> Option<Vec<Vec<(usize, usize)>>> {
+        let (problem, placements) = self.exact_cover(shapes);
+
+        problem.find().map(|solution| {
+            solution
+                .into_iter()
+                .map(|i| placements[i].clone())
+                .collect()
+        })
+    }
+}
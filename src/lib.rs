@@ -1,4 +1,22 @@
+pub mod binary;
 pub mod board;
+pub mod certificate;
+pub mod cover;
+pub mod cube;
+pub mod export;
+pub mod flip;
 pub mod graph;
+pub mod heatmap;
+pub mod height;
+pub mod hex;
+pub mod placement;
+pub mod polynomial;
 pub mod render;
+pub mod sampler;
+pub mod selector;
+pub mod sweep;
+pub mod symmetry;
 pub mod tile;
+pub mod tiling;
+pub mod topology;
+pub mod triangle;
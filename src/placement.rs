@@ -0,0 +1,163 @@
+use crate::board::RectangularBoard;
+use crate::tile::TileCollection;
+use crate::topology::Topology;
+use std::collections::HashSet;
+
+/// A way of placing down a tile on an empty board
+#[derive(Clone, Debug)]
+pub struct Placement {
+    /// The shape of the tile, as defined by the tile collection
+    pub shape: usize,
+
+    /// The `(row, col)` positions covered by the tile, in row-major order
+    pub positions: Vec<(usize, usize)>,
+
+    // The covered positions as a bitmask (see `RectangularBoard::mask`), stored as
+    // `(index, word)` pairs for the words which are nonzero
+    mask: Vec<(usize, u64)>,
+}
+
+impl Placement {
+    /// Determines whether this placement only covers positions which are unmarked on the given board
+    pub fn fits(&self, board: &RectangularBoard) -> bool {
+        let board_mask = board.mask();
+
+        self.mask
+            .iter()
+            .all(|(index, word)| board_mask[*index] & word == 0)
+    }
+
+    /// Returns the covered positions as `(index, word)` pairs of nonzero bitmask words
+    pub(crate) fn mask(&self) -> &[(usize, u64)] {
+        &self.mask
+    }
+}
+
+/// Every way of placing down a tile from a collection on an empty board of a given size and topology,
+/// indexed by the positions they cover.
+///
+/// Finding the ways of covering a position on a partially tiled board is then just a matter of
+/// checking which of these placements fit, which we can do with a few bitwise ANDs rather than
+/// walking around the board.  Use `TileCollection::placement_table` to get a table, since this
+/// caches the table for each board size and topology.
+#[derive(Clone, Debug)]
+pub struct PlacementTable {
+    pub width: usize,
+    pub height: usize,
+    pub topology: Topology,
+
+    placements: Vec<Placement>,
+
+    // For each position (in row-major order), the indices of the placements covering it
+    covering: Vec<Vec<usize>>,
+}
+
+impl PlacementTable {
+    /// Computes the placement table for a board of the given size and topology.  Forbidden
+    /// placements and positions are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::placement::PlacementTable;
+    /// use dcc_tiler::selector::SearchOptions;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::topology::Topology;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    /// let table = PlacementTable::new(3, 2, Topology::Plane, &tiles);
+    ///
+    /// // 4 horizontal and 3 vertical dominoes
+    /// assert_eq!(table.len(), 7);
+    /// assert_eq!(table.covering((0, 1)).count(), 3);
+    ///
+    /// let board = RectangularBoard::new(3, 2).place_tile_at(&[(0, 0)]).unwrap();
+    /// assert_eq!(table.covering((0, 1)).filter(|p| p.fits(&board)).count(), 2);
+    ///
+    /// // on a cylinder, two more horizontal dominoes wrap around
+    /// let table = PlacementTable::new(3, 2, Topology::Cylinder, &tiles);
+    /// assert_eq!(table.len(), 9);
+    ///
+    /// // T-tetrominoes stay connected when they cross the flipped edge of a Möbius strip
+    /// let tiles = TileCollection::from(Tile::t_tile(1));
+    /// assert_eq!(PlacementTable::new(5, 5, Topology::Mobius, &tiles).len(), 70);
+    ///
+    /// // and the numbers of tilings of an 8x4 Möbius strip and Klein bottle by them
+    /// fn count(board: &RectangularBoard, tiles: &TileCollection) -> usize {
+    ///     if board.is_all_marked() {
+    ///         return 1;
+    ///     }
+    ///
+    ///     let boards = board.place_tile(tiles, &SearchOptions::default());
+    ///     boards.iter().map(|board| count(board, tiles)).sum()
+    /// }
+    ///
+    /// let board = RectangularBoard::new(8, 4);
+    /// assert_eq!(count(&board.with_topology(Topology::Mobius), &tiles), 36);
+    /// assert_eq!(count(&board.with_topology(Topology::Klein), &tiles), 2564);
+    /// ```
+    pub fn new(width: usize, height: usize, topology: Topology, tiles: &TileCollection) -> Self {
+        let board = RectangularBoard::new(width, height).with_topology(topology);
+        let mut placements: Vec<Placement> = Vec::new();
+        let mut covering = vec![Vec::new(); width * height];
+        let mut seen = HashSet::new();
+
+        for row in 0..height {
+            for col in 0..width {
+                for (shape, positions) in board.placements_covering((row, col), tiles) {
+                    if !seen.insert(positions.clone()) {
+                        continue;
+                    }
+
+                    let mut mask: Vec<(usize, u64)> = Vec::new();
+
+                    for (r, c) in positions.iter() {
+                        let bit = r * width + c;
+                        covering[bit].push(placements.len());
+
+                        match mask.iter_mut().find(|(index, _)| *index == bit / 64) {
+                            Some((_, word)) => *word |= 1 << (bit % 64),
+                            None => mask.push((bit / 64, 1 << (bit % 64))),
+                        }
+                    }
+
+                    placements.push(Placement {
+                        shape,
+                        positions,
+                        mask,
+                    });
+                }
+            }
+        }
+
+        PlacementTable {
+            width,
+            height,
+            topology,
+            placements,
+            covering,
+        }
+    }
+
+    /// Returns the number of placements in the table
+    pub fn len(&self) -> usize {
+        self.placements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+
+    /// Returns all the placements in the table
+    pub fn iter(&self) -> impl Iterator<Item = &Placement> {
+        self.placements.iter()
+    }
+
+    /// Returns the placements covering the given `(row, col)` position
+    pub fn covering(&self, position: (usize, usize)) -> impl Iterator<Item = &Placement> {
+        self.covering[position.0 * self.width + position.1]
+            .iter()
+            .map(move |i| &self.placements[*i])
+    }
+}
@@ -0,0 +1,140 @@
+use num::{BigUint, One, Zero};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::AddAssign;
+
+/// A multivariate polynomial with non-negative integer coefficients.
+///
+/// Monomials are represented by their exponent vectors, so that `x0^a x1^b` is `[a, b]`.
+/// Exponent vectors never have trailing zeros, which lets us work with polynomials without
+/// knowing in advance how many variables there are.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polynomial {
+    terms: BTreeMap<Vec<usize>, BigUint>,
+}
+
+impl Polynomial {
+    /// Returns the zero polynomial
+    pub fn zero() -> Self {
+        Polynomial {
+            terms: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the constant polynomial 1
+    pub fn one() -> Self {
+        let mut terms = BTreeMap::new();
+        terms.insert(Vec::new(), BigUint::one());
+
+        Polynomial { terms }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns the coefficient of the monomial with the given exponents
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::polynomial::Polynomial;
+    /// use num::BigUint;
+    ///
+    /// let p = Polynomial::one().mul_var(1).mul_var(1);
+    /// assert_eq!(p.coefficient(&[0, 2]), BigUint::from(1u32));
+    /// assert_eq!(p.coefficient(&[0, 2, 0]), BigUint::from(1u32));
+    /// assert_eq!(p.coefficient(&[1]), BigUint::from(0u32));
+    /// ```
+    pub fn coefficient(&self, exponents: &[usize]) -> BigUint {
+        self.terms
+            .get(trim(exponents))
+            .cloned()
+            .unwrap_or_else(BigUint::zero)
+    }
+
+    /// Returns a copy of this polynomial multiplied by the specified variable
+    pub fn mul_var(&self, var: usize) -> Self {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(exponents, coefficient)| {
+                let mut exponents = exponents.clone();
+                if exponents.len() <= var {
+                    exponents.resize(var + 1, 0);
+                }
+                exponents[var] += 1;
+
+                (exponents, coefficient.clone())
+            })
+            .collect();
+
+        Polynomial { terms }
+    }
+
+    /// Returns the sum of all coefficients, i.e. the value of the polynomial
+    /// when every variable is set to 1
+    pub fn sum_of_coefficients(&self) -> BigUint {
+        self.terms.values().sum()
+    }
+
+    /// Iterates over the `(exponents, coefficient)` pairs of the nonzero terms of this polynomial
+    pub fn terms<'b>(&'b self) -> Box<dyn Iterator<Item = (&'b [usize], &'b BigUint)> + 'b> {
+        Box::new(self.terms.iter().map(|(e, c)| (e.as_slice(), c)))
+    }
+}
+
+/// Removes trailing zeros from an exponent vector
+fn trim(exponents: &[usize]) -> &[usize] {
+    let len = exponents.iter().rposition(|e| *e != 0).map_or(0, |i| i + 1);
+
+    &exponents[..len]
+}
+
+impl AddAssign<&Polynomial> for Polynomial {
+    fn add_assign(&mut self, other: &Polynomial) {
+        for (exponents, coefficient) in other.terms.iter() {
+            *self
+                .terms
+                .entry(exponents.clone())
+                .or_insert_with(BigUint::zero) += coefficient;
+        }
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const NAMES: [&str; 4] = ["x", "y", "z", "w"];
+
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut terms = Vec::new();
+
+        for (exponents, coefficient) in self.terms.iter() {
+            let mut factors = Vec::new();
+
+            if !coefficient.is_one() || exponents.is_empty() {
+                factors.push(coefficient.to_string());
+            }
+
+            for (var, exponent) in exponents.iter().enumerate() {
+                let name = match NAMES.get(var) {
+                    Some(name) => name.to_string(),
+                    None => format!("x{}", var),
+                };
+
+                match exponent {
+                    0 => {}
+                    1 => factors.push(name),
+                    _ => factors.push(format!("{}^{}", name, exponent)),
+                }
+            }
+
+            terms.push(factors.join("*"));
+        }
+
+        write!(f, "{}", terms.join(" + "))
+    }
+}
@@ -1,9 +1,33 @@
 use crate::board::RectangularBoard;
+use crate::certificate::Certificate;
+use crate::cube::{CubeBoard, CubeDirection, Position3};
+use crate::heatmap::Heatmap;
+use crate::height::HeightFunction;
+use crate::hex::{HexBoard, HexDirection};
+use crate::tile::Direction;
+use crate::tiling::Tiling;
+use crate::triangle::{self, TriangleBoard, TriangleDirection};
 use rand::seq::SliceRandom;
 use rand::Rng;
-use simplesvg::{Attr, Color, Fig, Svg};
+use simplesvg::{Attr, Color, ColorAttr, Fig, Svg};
 use std::collections::{HashMap, HashSet};
 
+// TODO: maybe remove GAP_SIZE now that we've implemented borders
+const GAP_SIZE: f32 = 0.0;
+const BOX_SIZE: f32 = 50.0;
+const PADDING: f32 = 10.0;
+
+// TODO: make these configurable
+const COLORS: [ColorAttr; 7] = [
+    Color(30, 56, 136),
+    Color(71, 115, 170),
+    Color(245, 230, 99),
+    Color(255, 173, 105),
+    Color(156, 56, 72),
+    Color(124, 178, 135),
+    Color(251, 219, 136),
+];
+
 pub fn render_single_tiling_from_vec(boards: Vec<&RectangularBoard>) -> String {
     let mut tile_hashmap = HashMap::new();
 
@@ -18,137 +42,755 @@ pub fn render_single_tiling<S: ::std::hash::BuildHasher>(
     board: &RectangularBoard,
     tile_hashmap: &HashMap<RectangularBoard, Vec<RectangularBoard>, S>,
 ) -> String {
-    // TODO: maybe remove gap_size now that we've implemented borders
-    let gap_size = 0.0;
-    let box_size = 50.0;
-    let padding = 10.0;
-
-    // TODO: make these configurable
-    let colors = vec![
-        Color(30, 56, 136),
-        Color(71, 115, 170),
-        Color(245, 230, 99),
-        Color(255, 173, 105),
-        Color(156, 56, 72),
-        Color(124, 178, 135),
-        Color(251, 219, 136),
-    ];
-
     let mut boxes = Vec::new();
     let mut rng = rand::thread_rng();
 
     // choose a random initial colour
     // we do this so that when you render a single tile, it won't always be the first colour in the colors vector
-    let mut color_index = rng.gen_range(0..colors.len());
+    let mut color_index = rng.gen_range(0..COLORS.len());
     let mut current = board;
 
-    while let Some(board) = tile_hashmap.get(current) {
+    while let Some(sources) = tile_hashmap.get(current) {
         // choose a random source for this board state
-        let next_board = board.choose(&mut rng).unwrap();
-        let mut tiled_positions = HashSet::new();
+        let next_board = sources.choose(&mut rng).unwrap();
 
         // compute the tile that was placed here
-        for y in 0..next_board.height {
-            for x in 0..next_board.width {
-                if next_board.board[y][x] ^ current.board[y][x] {
-                    // we just tiled this position
-                    tiled_positions.insert((x, y));
-                }
+        let tiled_positions = tiled_positions(next_board, current);
+        draw_tile(&mut boxes, board, &tiled_positions, COLORS[color_index]);
+
+        // increment the color index by 1
+        color_index = (color_index + 1) % COLORS.len();
+
+        current = next_board;
+    }
+
+    draw_gluing(&mut boxes, board);
+    to_svg(boxes, board.width, board.height)
+}
+
+/// Renders a packing of a board, given as a sequence of boards (as for `render_single_tiling_from_vec`)
+/// together with the `(row, col)` positions that were left as holes instead of being covered by a tile.
+pub fn render_packing(boards: Vec<&RectangularBoard>, holes: &HashSet<(usize, usize)>) -> String {
+    let mut boxes = Vec::new();
+    let mut color_index = rand::thread_rng().gen_range(0..COLORS.len());
+    let board = boards.last().unwrap();
+
+    for i in 1..boards.len() {
+        let tiled_positions = tiled_positions(boards[i - 1], boards[i]);
+
+        if tiled_positions.len() == 1
+            && tiled_positions
+                .iter()
+                .all(|(x, y)| holes.contains(&(*y, *x)))
+        {
+            for (x, y) in tiled_positions {
+                draw_hole(&mut boxes, x, y);
             }
+        } else {
+            draw_tile(&mut boxes, board, &tiled_positions, COLORS[color_index]);
+            color_index = (color_index + 1) % COLORS.len();
         }
+    }
 
-        for (x, y) in tiled_positions.iter() {
-            // draw the underlying box
-            let rect = Fig::Rect(
-                (*x as f32) * (box_size + gap_size) + padding,
-                (*y as f32) * (box_size + gap_size) + padding,
-                box_size,
-                box_size,
-            )
-            .styled(Attr::default().fill(colors[color_index]));
-
-            boxes.push(rect);
-
-            enum Border {
-                Left,
-                Right,
-                Top,
-                Bottom,
-            }
+    draw_gluing(&mut boxes, board);
+    to_svg(boxes, board.width, board.height)
+}
+
+/// Renders a tiling.  Unlike `render_single_tiling_from_vec`, this doesn't need a board for
+/// each tile, so it can be used for tilings of large boards.
+pub fn render_tiling(tiling: &Tiling) -> String {
+    let mut boxes = Vec::new();
+    let board = tiling.initial_board();
+
+    for (i, tile) in tiling.tiles().into_iter().enumerate() {
+        let tiled_positions = tile.into_iter().map(|(row, col)| (col, row)).collect();
+        draw_tile(
+            &mut boxes,
+            &board,
+            &tiled_positions,
+            COLORS[i % COLORS.len()],
+        );
+    }
 
-            // helper function to construct our borders
-            let border = |x: usize, y: usize, b: Border, gray: bool| {
-                let xs = match b {
-                    Border::Right => (x as f32 + 1.0) * (box_size + gap_size) + padding - gap_size,
-                    _ => (x as f32) * (box_size + gap_size) + padding,
-                };
-                let ys = match b {
-                    Border::Top => (y as f32 + 1.0) * (box_size + gap_size) + padding - gap_size,
-                    _ => (y as f32) * (box_size + gap_size) + padding,
-                };
-                let xe = match b {
-                    Border::Left => (x as f32) * (box_size + gap_size) + padding,
-                    _ => (x as f32 + 1.0) * (box_size + gap_size) + padding - gap_size,
-                };
-                let ye = match b {
-                    Border::Bottom => (y as f32) * (box_size + gap_size) + padding,
-                    _ => (y as f32 + 1.0) * (box_size + gap_size) + padding - gap_size,
-                };
-
-                let mut b = Fig::Line(xs, ys, xe, ye);
-                b = b.styled(
+    to_svg(boxes, tiling.width, tiling.height)
+}
+
+/// Renders a heatmap, with one copy of the board for each tile orientation.
+///
+/// Each position is shaded (and labelled) by the probability that a uniformly random tiling
+/// covers it by a tile with that orientation, and the orientation itself is drawn above its board.
+pub fn render_heatmap(heatmap: &Heatmap) -> String {
+    const PREVIEW_BOX_SIZE: f32 = 10.0;
+
+    let mut boxes = Vec::new();
+
+    let board_width = (heatmap.width as f32) * (BOX_SIZE + GAP_SIZE) + 2.0 * PADDING;
+    let preview_height = heatmap
+        .orientations
+        .iter()
+        .flatten()
+        .map(|(row, _)| (*row + 1) as f32 * PREVIEW_BOX_SIZE)
+        .fold(0.0, f32::max)
+        + PADDING;
+
+    for (o, orientation) in heatmap.orientations.iter().enumerate() {
+        let xo = (o as f32) * board_width;
+
+        // draw the orientation above its board
+        for (row, col) in orientation.iter() {
+            boxes.push(
+                Fig::Rect(
+                    xo + PADDING + (*col as f32) * PREVIEW_BOX_SIZE,
+                    PADDING + (*row as f32) * PREVIEW_BOX_SIZE,
+                    PREVIEW_BOX_SIZE,
+                    PREVIEW_BOX_SIZE,
+                )
+                .styled(
                     Attr::default()
-                        .stroke(if gray {
-                            Color(211, 211, 211)
+                        .fill(COLORS[0])
+                        .stroke(Color(0, 0, 0))
+                        .stroke_width(0.5),
+                ),
+            );
+        }
+
+        for y in 0..heatmap.height {
+            for x in 0..heatmap.width {
+                let xs = xo + (x as f32) * (BOX_SIZE + GAP_SIZE) + PADDING;
+                let ys = preview_height + (y as f32) * (BOX_SIZE + GAP_SIZE) + PADDING;
+
+                if heatmap.marked[y][x] {
+                    boxes.push(
+                        Fig::Rect(xs, ys, BOX_SIZE, BOX_SIZE).styled(
+                            Attr::default()
+                                .fill(Color(211, 211, 211))
+                                .stroke(Color(160, 160, 160))
+                                .stroke_width(0.5),
+                        ),
+                    );
+                    continue;
+                }
+
+                let p = heatmap.probability(o, (y, x));
+
+                // interpolate between white and our darkest colour
+                let shade = |c: u8| (255.0 - p * (255.0 - c as f64)).round() as u8;
+
+                boxes.push(
+                    Fig::Rect(xs, ys, BOX_SIZE, BOX_SIZE).styled(
+                        Attr::default()
+                            .fill(Color(shade(30), shade(56), shade(136)))
+                            .stroke(Color(0, 0, 0))
+                            .stroke_width(0.5),
+                    ),
+                );
+                boxes.push(
+                    Fig::Text(xs + 5.0, ys + BOX_SIZE / 2.0 + 5.0, format!("{:.2}", p)).styled(
+                        Attr::default().fill(if p > 0.5 {
+                            Color(255, 255, 255)
                         } else {
                             Color(0, 0, 0)
-                        })
-                        .stroke_width(0.5),
+                        }),
+                    ),
                 );
+            }
+        }
+    }
 
-                b
+    Svg(
+        vec![Fig::Multiple(boxes)],
+        (board_width * heatmap.orientations.len() as f32) as u32,
+        (preview_height + (heatmap.height as f32) * (BOX_SIZE + GAP_SIZE) + 2.0 * PADDING) as u32,
+    )
+    .to_string()
+}
+
+/// Renders a height function as a surface, where each position is coloured according to the average
+/// height of its corners (from dark blue for the lowest to yellow for the highest), and the
+/// tiles of the tiling are outlined.
+pub fn render_height_function(heights: &HeightFunction, tiling: &Tiling) -> String {
+    let mut boxes = Vec::new();
+    let (low, high) = heights.range().unwrap_or((0, 0));
+
+    let (from, to) = ((30.0, 56.0, 136.0), (245.0, 230.0, 99.0));
+    let lerp = |a: f64, b: f64, t: f64| (a + t * (b - a)).round() as u8;
+
+    for row in 0..tiling.height {
+        for col in 0..tiling.width {
+            if !tiling.is_covered((row, col)) {
+                continue;
+            }
+
+            let corners = [
+                (row, col),
+                (row + 1, col),
+                (row, col + 1),
+                (row + 1, col + 1),
+            ];
+            let mean = corners
+                .iter()
+                .map(|v| heights.get(*v).unwrap_or(0) as f64)
+                .sum::<f64>()
+                / 4.0;
+
+            let t = if high > low {
+                (mean - low as f64) / (high - low) as f64
+            } else {
+                0.5
             };
 
-            // left border
-            boxes.push(border(
-                *x,
-                *y,
-                Border::Left,
-                *x == 0 || tiled_positions.contains(&(*x - 1, *y)),
-            ));
-            // right border
-            boxes.push(border(
-                *x,
-                *y,
-                Border::Right,
-                tiled_positions.contains(&(*x + 1, *y)),
-            ));
-            // top border
-            boxes.push(border(
-                *x,
-                *y,
-                Border::Top,
-                tiled_positions.contains(&(*x, *y + 1)),
-            ));
-            // bottom border
-            boxes.push(border(
-                *x,
-                *y,
-                Border::Bottom,
-                *y == 0 || tiled_positions.contains(&(*x, *y - 1)),
+            boxes.push(
+                Fig::Rect(
+                    (col as f32) * (BOX_SIZE + GAP_SIZE) + PADDING,
+                    (row as f32) * (BOX_SIZE + GAP_SIZE) + PADDING,
+                    BOX_SIZE,
+                    BOX_SIZE,
+                )
+                .styled(Attr::default().fill(Color(
+                    lerp(from.0, to.0, t),
+                    lerp(from.1, to.1, t),
+                    lerp(from.2, to.2, t),
+                ))),
+            );
+        }
+    }
+
+    // outline the tiles, by drawing the sides of each position which aren't shared with the same tile
+    let stroke = Attr::default().stroke(Color(0, 0, 0)).stroke_width(1.0);
+
+    for row in 0..tiling.height {
+        for col in 0..tiling.width {
+            if !tiling.is_covered((row, col)) {
+                continue;
+            }
+
+            let xs = (col as f32) * (BOX_SIZE + GAP_SIZE) + PADDING;
+            let ys = (row as f32) * (BOX_SIZE + GAP_SIZE) + PADDING;
+
+            if row == 0 || !tiling.same_tile((row, col), (row - 1, col)) {
+                boxes.push(Fig::Line(xs, ys, xs + BOX_SIZE, ys).styled(stroke.clone()));
+            }
+            if col == 0 || !tiling.same_tile((row, col), (row, col - 1)) {
+                boxes.push(Fig::Line(xs, ys, xs, ys + BOX_SIZE).styled(stroke.clone()));
+            }
+            if row + 1 == tiling.height || !tiling.same_tile((row, col), (row + 1, col)) {
+                boxes.push(
+                    Fig::Line(xs, ys + BOX_SIZE, xs + BOX_SIZE, ys + BOX_SIZE)
+                        .styled(stroke.clone()),
+                );
+            }
+            if col + 1 == tiling.width || !tiling.same_tile((row, col), (row, col + 1)) {
+                boxes.push(
+                    Fig::Line(xs + BOX_SIZE, ys, xs + BOX_SIZE, ys + BOX_SIZE)
+                        .styled(stroke.clone()),
+                );
+            }
+        }
+    }
+
+    to_svg(boxes, tiling.width, tiling.height)
+}
+
+/// Renders a colouring argument, where each position is filled in with its colour
+/// and labelled by its weight.  Positions which are not part of the board are drawn in gray.
+pub fn render_certificate(certificate: &Certificate) -> String {
+    let mut boxes = Vec::new();
+
+    for row in 0..certificate.height {
+        for col in 0..certificate.width {
+            let xs = (col as f32) * (BOX_SIZE + GAP_SIZE) + PADDING;
+            let ys = (row as f32) * (BOX_SIZE + GAP_SIZE) + PADDING;
+
+            if certificate.marked[row][col] {
+                boxes.push(
+                    Fig::Rect(xs, ys, BOX_SIZE, BOX_SIZE).styled(
+                        Attr::default()
+                            .fill(Color(211, 211, 211))
+                            .stroke(Color(160, 160, 160))
+                            .stroke_width(0.5),
+                    ),
+                );
+                continue;
+            }
+
+            let colour = certificate.colouring.colour((row, col));
+
+            boxes.push(
+                Fig::Rect(xs, ys, BOX_SIZE, BOX_SIZE).styled(
+                    Attr::default()
+                        .fill(COLORS[colour % COLORS.len()])
+                        .stroke(Color(0, 0, 0))
+                        .stroke_width(0.5),
+                ),
+            );
+            boxes.push(
+                Fig::Text(
+                    xs + BOX_SIZE / 2.0 - 4.0,
+                    ys + BOX_SIZE / 2.0 + 5.0,
+                    certificate.weight((row, col)).to_string(),
+                )
+                .styled(Attr::default().fill(
+                    // the first two colours are dark
+                    if colour % COLORS.len() < 2 {
+                        Color(255, 255, 255)
+                    } else {
+                        Color(0, 0, 0)
+                    },
+                )),
+            );
+        }
+    }
+
+    to_svg(boxes, certificate.width, certificate.height)
+}
+
+/// Renders a tiling of a hexagonal board, given by the `(row, col)` positions covered by
+/// each tile (as returned by `HexBoard::find_tiling`)
+pub fn render_hex_tiling(board: &HexBoard, tiles: &[Vec<(usize, usize)>]) -> String {
+    // the distance from the centre of a hexagon to its corners,
+    // so that the centres of neighbouring hexagons are BOX_SIZE apart
+    let radius = BOX_SIZE / 3f32.sqrt();
+
+    // the neighbours across each side of a hexagon, starting from the side
+    // between the first two corners and going clockwise
+    let sides = [
+        HexDirection::East,
+        HexDirection::SouthEast,
+        HexDirection::SouthWest,
+        HexDirection::West,
+        HexDirection::NorthWest,
+        HexDirection::NorthEast,
+    ];
+
+    render_polygon_tiling(
+        tiles,
+        |(row, col)| {
+            let x = (col as f32 + row as f32 / 2.0) * BOX_SIZE;
+            let y = row as f32 * 1.5 * radius;
+
+            (0..6)
+                .map(|i| {
+                    let angle = (60.0 * i as f32 - 30.0).to_radians();
+                    (x + radius * angle.cos(), y + radius * angle.sin())
+                })
+                .collect()
+        },
+        |position, side| board.neighbour(position, sides[side]),
+    )
+}
+
+/// Renders a tiling of a triangular board, given by the `(row, col)` positions covered by
+/// each tile (as returned by `TriangleBoard::find_tiling`)
+pub fn render_triangle_tiling(board: &TriangleBoard, tiles: &[Vec<(usize, usize)>]) -> String {
+    // the sides between consecutive corners returned by `triangle::corners`
+    let sides = [
+        TriangleDirection::Vertical,
+        TriangleDirection::Right,
+        TriangleDirection::Left,
+    ];
+
+    render_polygon_tiling(
+        tiles,
+        |(row, col)| {
+            triangle::corners((row as isize, col as isize))
+                .iter()
+                .map(|(x, row)| {
+                    (
+                        *x as f32 * BOX_SIZE / 2.0,
+                        *row as f32 * BOX_SIZE * 3f32.sqrt() / 2.0,
+                    )
+                })
+                .collect()
+        },
+        |position, side| board.neighbour(position, sides[side]),
+    )
+}
+
+/// Renders a tiling of a box of cubes as text, given by the `(layer, row, col)` positions
+/// covered by each tile (as returned by `CubeBoard::find_tiling`).  Each layer is drawn as a
+/// grid, front layer first, with each tile labelled by a letter and marked cells shown by `#`.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::cube::{CubeBoard, CubeTile};
+/// use dcc_tiler::render::render_cube_layers;
+///
+/// let board = CubeBoard::cuboid(2, 1, 2);
+/// let tiling = board.find_tiling(&[CubeTile::bar(2)]).unwrap();
+/// assert_eq!(render_cube_layers(&board, &tiling), "layer 0\nAA\n\nlayer 1\nBB\n");
+/// ```
+pub fn render_cube_layers(board: &CubeBoard, tiles: &[Vec<Position3>]) -> String {
+    let labels: Vec<char> = ('A'..='Z').chain('a'..='z').collect();
+    let mut grid = vec![vec![vec!['.'; board.width]; board.height]; board.depth];
+
+    for (layer, rows) in board.board.iter().enumerate() {
+        for (row, marks) in rows.iter().enumerate() {
+            for (col, marked) in marks.iter().enumerate() {
+                if *marked {
+                    grid[layer][row][col] = '#';
+                }
+            }
+        }
+    }
+
+    for (i, tile) in tiles.iter().enumerate() {
+        for (layer, row, col) in tile {
+            grid[*layer][*row][*col] = labels[i % labels.len()];
+        }
+    }
+
+    grid.iter()
+        .enumerate()
+        .map(|(layer, rows)| {
+            let rows: Vec<String> = rows.iter().map(|row| row.iter().collect()).collect();
+            format!("layer {}\n{}\n", layer, rows.join("\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders a tiling of a box of cubes (as for `render_cube_layers`) as a Wavefront OBJ model,
+/// with one object for each tile.  Only the outside faces of each tile are included, so tiles
+/// look solid.  The x, y and z axes point right, up and towards the viewer respectively.
+pub fn render_cube_obj(board: &CubeBoard, tiles: &[Vec<Position3>]) -> String {
+    // the corners of each face of a unit cube, anticlockwise when seen from outside
+    let faces = [
+        (
+            CubeDirection::Right,
+            [(1, 0, 0), (1, 1, 0), (1, 1, 1), (1, 0, 1)],
+        ),
+        (
+            CubeDirection::Left,
+            [(0, 0, 0), (0, 0, 1), (0, 1, 1), (0, 1, 0)],
+        ),
+        (
+            CubeDirection::Up,
+            [(0, 1, 0), (0, 1, 1), (1, 1, 1), (1, 1, 0)],
+        ),
+        (
+            CubeDirection::Down,
+            [(0, 0, 0), (1, 0, 0), (1, 0, 1), (0, 0, 1)],
+        ),
+        (
+            CubeDirection::Front,
+            [(0, 0, 1), (1, 0, 1), (1, 1, 1), (0, 1, 1)],
+        ),
+        (
+            CubeDirection::Back,
+            [(0, 0, 0), (0, 1, 0), (1, 1, 0), (1, 0, 0)],
+        ),
+    ];
+
+    let mut obj = String::new();
+    let mut vertices = 0;
+
+    for (i, tile) in tiles.iter().enumerate() {
+        obj += &format!("o tile_{}\n", i);
+
+        for position in tile {
+            let (layer, row, col) = *position;
+            let origin = (
+                col as isize,
+                (board.height - 1 - row) as isize,
+                -(layer as isize) - 1,
+            );
+
+            for (direction, corners) in faces.iter() {
+                let inside = board
+                    .neighbour(*position, *direction)
+                    .is_some_and(|p| tile.contains(&p));
+
+                if inside {
+                    continue;
+                }
+
+                for (x, y, z) in corners.iter() {
+                    obj += &format!("v {} {} {}\n", origin.0 + x, origin.1 + y, origin.2 + z);
+                }
+                obj += &format!(
+                    "f {} {} {} {}\n",
+                    vertices + 1,
+                    vertices + 2,
+                    vertices + 3,
+                    vertices + 4
+                );
+                vertices += 4;
+            }
+        }
+    }
+
+    obj
+}
+
+/// Renders a tiling of a board whose cells are polygons.  `corners` returns the corners of the
+/// cell at a `(row, col)` position, going around the cell, and `across` returns the position
+/// on the other side of the side starting at the `i`-th corner, if any.
+fn render_polygon_tiling<C, A>(tiles: &[Vec<(usize, usize)>], corners: C, across: A) -> String
+where
+    C: Fn((usize, usize)) -> Vec<(f32, f32)>,
+    A: Fn((usize, usize), usize) -> Option<(usize, usize)>,
+{
+    let hex = |color: ColorAttr| match color {
+        Color(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        _ => "none".to_string(),
+    };
+
+    let cells: Vec<Vec<(f32, f32)>> = tiles.iter().flatten().map(|p| corners(*p)).collect();
+    let min_x = cells
+        .iter()
+        .flatten()
+        .map(|c| c.0)
+        .fold(f32::INFINITY, f32::min);
+    let min_y = cells
+        .iter()
+        .flatten()
+        .map(|c| c.1)
+        .fold(f32::INFINITY, f32::min);
+    let max_x = cells.iter().flatten().map(|c| c.0).fold(0.0, f32::max);
+    let max_y = cells.iter().flatten().map(|c| c.1).fold(0.0, f32::max);
+
+    // move the top left corner of the tiling to (PADDING, PADDING)
+    let point = |(x, y): (f32, f32)| (x - min_x + PADDING, y - min_y + PADDING);
+
+    let mut svg = format!(
+        "<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">\n",
+        (max_x - min_x + 2.0 * PADDING).ceil(),
+        (max_y - min_y + 2.0 * PADDING).ceil()
+    );
+    let mut borders = String::new();
+
+    for (i, tile) in tiles.iter().enumerate() {
+        let color = hex(COLORS[i % COLORS.len()]);
+
+        for position in tile.iter() {
+            let corners: Vec<_> = corners(*position).into_iter().map(point).collect();
+            let points: Vec<String> = corners
+                .iter()
+                .map(|(x, y)| format!("{},{}", x, y))
+                .collect();
+
+            svg.push_str(&format!(
+                "<polygon points=\"{}\" style=\"fill:{};\"/>\n",
+                points.join(" "),
+                color
             ));
+
+            // sides shared with another cell of the same tile are drawn in gray
+            for side in 0..corners.len() {
+                let (x1, y1) = corners[side];
+                let (x2, y2) = corners[(side + 1) % corners.len()];
+                let joined = across(*position, side).is_some_and(|p| tile.contains(&p));
+
+                borders.push_str(&format!(
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" style=\"stroke:{};stroke-width:0.5;\"/>\n",
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    hex(if joined {
+                        Color(211, 211, 211)
+                    } else {
+                        Color(0, 0, 0)
+                    })
+                ));
+            }
         }
+    }
 
-        // increment the color index by 1
-        color_index = (color_index + 1) % colors.len();
+    // draw the borders on top of all of the cells
+    svg.push_str(&borders);
+    svg.push_str("</svg>\n");
+    svg
+}
 
-        current = next_board;
+/// Returns the `(x, y)` positions which are marked in one board but not the other
+fn tiled_positions(a: &RectangularBoard, b: &RectangularBoard) -> HashSet<(usize, usize)> {
+    let mut tiled_positions = HashSet::new();
+
+    for y in 0..a.height {
+        for x in 0..a.width {
+            if a.board[y][x] ^ b.board[y][x] {
+                // we just tiled this position
+                tiled_positions.insert((x, y));
+            }
+        }
     }
 
+    tiled_positions
+}
+
+fn to_svg(boxes: Vec<Fig>, width: usize, height: usize) -> String {
     Svg(
         vec![Fig::Multiple(boxes)],
-        (50 * board.width) as u32 + 2 * (padding as u32),
-        (50 * board.height) as u32 + 2 * (padding as u32),
+        (50 * width) as u32 + 2 * (PADDING as u32),
+        (50 * height) as u32 + 2 * (PADDING as u32),
     )
     .to_string()
 }
+
+/// Draws an empty box (with a cross through it) at the given position
+fn draw_hole(boxes: &mut Vec<Fig>, x: usize, y: usize) {
+    let xs = (x as f32) * (BOX_SIZE + GAP_SIZE) + PADDING;
+    let ys = (y as f32) * (BOX_SIZE + GAP_SIZE) + PADDING;
+    let stroke = Attr::default()
+        .stroke(Color(160, 160, 160))
+        .stroke_width(0.5);
+
+    boxes.push(
+        Fig::Rect(xs, ys, BOX_SIZE, BOX_SIZE).styled(
+            Attr::default()
+                .fill(Color(255, 255, 255))
+                .stroke(Color(0, 0, 0))
+                .stroke_width(0.5),
+        ),
+    );
+    boxes.push(Fig::Line(xs, ys, xs + BOX_SIZE, ys + BOX_SIZE).styled(stroke.clone()));
+    boxes.push(Fig::Line(xs + BOX_SIZE, ys, xs, ys + BOX_SIZE).styled(stroke));
+}
+
+/// Draws arrows on the edges of the board which are glued together, in the usual way: edges with
+/// the same number of arrowheads are glued so that the arrows line up
+fn draw_gluing(boxes: &mut Vec<Fig>, board: &RectangularBoard) {
+    let width = board.width as f32 * (BOX_SIZE + GAP_SIZE);
+    let height = board.height as f32 * (BOX_SIZE + GAP_SIZE);
+    let style = Attr::default().stroke(Color(0, 0, 0)).stroke_width(1.5);
+
+    // an arrowhead centred at (x, y), pointing in the direction (dx, dy)
+    let arrowhead = |x: f32, y: f32, dx: f32, dy: f32| {
+        let size = PADDING * 0.6;
+
+        Fig::Multiple(vec![
+            Fig::Line(
+                x + size * dx,
+                y + size * dy,
+                x - size * (dx + dy),
+                y - size * (dy - dx),
+            ),
+            Fig::Line(
+                x + size * dx,
+                y + size * dy,
+                x - size * (dx - dy),
+                y - size * (dy + dx),
+            ),
+        ])
+        .styled(style.clone())
+    };
+
+    if board.topology.wraps_columns() {
+        let y = PADDING + height / 2.0;
+        let right = if board.topology.flips() { -1.0 } else { 1.0 };
+
+        boxes.push(arrowhead(PADDING, y, 0.0, 1.0));
+        boxes.push(arrowhead(PADDING + width, y, 0.0, right));
+    }
+
+    if board.topology.wraps_rows() {
+        let x = PADDING + width / 2.0;
+
+        // two arrowheads, to tell these edges apart from the left and right ones
+        for y in [PADDING, PADDING + height] {
+            boxes.push(arrowhead(x - PADDING * 0.5, y, 1.0, 0.0));
+            boxes.push(arrowhead(x + PADDING * 0.5, y, 1.0, 0.0));
+        }
+    }
+}
+
+/// Draws a single tile covering the given `(x, y)` positions of the board
+fn draw_tile(
+    boxes: &mut Vec<Fig>,
+    board: &RectangularBoard,
+    tiled_positions: &HashSet<(usize, usize)>,
+    color: ColorAttr,
+) {
+    // whether the side of (x, y) in the given direction is shared with another position of the
+    // tile, possibly across a glued edge.  The left and bottom edges of the board have always
+    // been drawn in gray.
+    let joined = |x: usize, y: usize, direction: Direction, on_edge: bool| {
+        board
+            .neighbour((y, x), direction)
+            .map_or(on_edge, |(row, col)| tiled_positions.contains(&(col, row)))
+    };
+
+    for (x, y) in tiled_positions.iter() {
+        // draw the underlying box
+        let rect = Fig::Rect(
+            (*x as f32) * (BOX_SIZE + GAP_SIZE) + PADDING,
+            (*y as f32) * (BOX_SIZE + GAP_SIZE) + PADDING,
+            BOX_SIZE,
+            BOX_SIZE,
+        )
+        .styled(Attr::default().fill(color));
+
+        boxes.push(rect);
+
+        enum Border {
+            Left,
+            Right,
+            Top,
+            Bottom,
+        }
+
+        // helper function to construct our borders
+        let border = |x: usize, y: usize, b: Border, gray: bool| {
+            let xs = match b {
+                Border::Right => (x as f32 + 1.0) * (BOX_SIZE + GAP_SIZE) + PADDING - GAP_SIZE,
+                _ => (x as f32) * (BOX_SIZE + GAP_SIZE) + PADDING,
+            };
+            let ys = match b {
+                Border::Top => (y as f32 + 1.0) * (BOX_SIZE + GAP_SIZE) + PADDING - GAP_SIZE,
+                _ => (y as f32) * (BOX_SIZE + GAP_SIZE) + PADDING,
+            };
+            let xe = match b {
+                Border::Left => (x as f32) * (BOX_SIZE + GAP_SIZE) + PADDING,
+                _ => (x as f32 + 1.0) * (BOX_SIZE + GAP_SIZE) + PADDING - GAP_SIZE,
+            };
+            let ye = match b {
+                Border::Bottom => (y as f32) * (BOX_SIZE + GAP_SIZE) + PADDING,
+                _ => (y as f32 + 1.0) * (BOX_SIZE + GAP_SIZE) + PADDING - GAP_SIZE,
+            };
+
+            let mut b = Fig::Line(xs, ys, xe, ye);
+            b = b.styled(
+                Attr::default()
+                    .stroke(if gray {
+                        Color(211, 211, 211)
+                    } else {
+                        Color(0, 0, 0)
+                    })
+                    .stroke_width(0.5),
+            );
+
+            b
+        };
+
+        // left border
+        boxes.push(border(
+            *x,
+            *y,
+            Border::Left,
+            joined(*x, *y, Direction::Left, true),
+        ));
+        // right border
+        boxes.push(border(
+            *x,
+            *y,
+            Border::Right,
+            joined(*x, *y, Direction::Right, false),
+        ));
+        // top border
+        boxes.push(border(
+            *x,
+            *y,
+            Border::Top,
+            joined(*x, *y, Direction::Down, false),
+        ));
+        // bottom border
+        boxes.push(border(
+            *x,
+            *y,
+            Border::Bottom,
+            joined(*x, *y, Direction::Up, true),
+        ));
+    }
+}
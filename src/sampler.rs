@@ -0,0 +1,89 @@
+use crate::tile::TileCollection;
+use crate::tiling::Tiling;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// A Markov chain on the tilings of a board, which at each step picks a window uniformly at
+/// random and re-tiles the tiles lying inside it uniformly at random.
+///
+/// The moves are symmetric, so the uniform distribution on tilings is stationary.  Whenever
+/// the flip graph (see `FlipGraph`) is connected, running the chain for long enough therefore
+/// gives an approximately uniformly random tiling, without having to enumerate every tiling.
+pub struct FlipSampler<R: Rng> {
+    tiling: Tiling,
+    tiles: TileCollection,
+
+    // The `(rows, cols)` of the windows we re-tile
+    window: (usize, usize),
+
+    rng: R,
+}
+
+impl<R: Rng> FlipSampler<R> {
+    /// Creates a chain starting at the given tiling, using windows with `(rows, cols)` given
+    /// by `window`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::sampler::FlipSampler;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiling::Tiling;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    /// let tiling = Tiling::search(&RectangularBoard::new(10, 10), &tiles).unwrap();
+    ///
+    /// let mut sampler = FlipSampler::new(tiling.clone(), tiles, (2, 2), StdRng::seed_from_u64(1));
+    /// sampler.run(1000);
+    ///
+    /// assert_ne!(*sampler.tiling(), tiling);
+    /// assert_eq!(sampler.tiling().tiles().len(), 50);
+    /// ```
+    pub fn new(tiling: Tiling, tiles: TileCollection, window: (usize, usize), rng: R) -> Self {
+        FlipSampler {
+            tiling,
+            tiles,
+            window,
+            rng,
+        }
+    }
+
+    /// Returns the current tiling
+    pub fn tiling(&self) -> &Tiling {
+        &self.tiling
+    }
+
+    /// Performs a single step of the chain.  Returns whether the tiling changed.
+    pub fn step(&mut self) -> bool {
+        let rows = self.tiling.height.saturating_sub(self.window.0) + 1;
+        let cols = self.tiling.width.saturating_sub(self.window.1) + 1;
+        let top_left = (self.rng.gen_range(0..rows), self.rng.gen_range(0..cols));
+
+        let retilings = self
+            .tiling
+            .window_retilings(&self.tiles, top_left, self.window);
+
+        match retilings.choose(&mut self.rng) {
+            // we might choose the tiles which are already there
+            Some(retiling) if retilings.len() > 1 => {
+                let changed = retiling
+                    .iter()
+                    .any(|tile| self.tiling.tile_at(tile[0]).as_ref() != Some(tile));
+
+                self.tiling.retile(retiling);
+                changed
+            }
+            _ => false,
+        }
+    }
+
+    /// Performs the given number of steps of the chain
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+}
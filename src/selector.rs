@@ -0,0 +1,155 @@
+use crate::board::RectangularBoard;
+use crate::tile::TileCollection;
+use std::fmt;
+use std::sync::Arc;
+
+/// Options controlling how `RectangularBoard::place_tile` extends a board
+///
+/// # Examples
+///
+/// ```rust
+/// use dcc_tiler::board::RectangularBoard;
+/// use dcc_tiler::selector::SearchOptions;
+/// use dcc_tiler::tile::{Tile, TileCollection};
+///
+/// // a 3x3 board with the middle column marked, leaving two regions of area 3
+/// let board = RectangularBoard::from_marks(vec![vec![false, true, false]; 3]);
+/// let tiles = TileCollection::from(Tile::l_tile(1));
+/// let mut options = SearchOptions::default();
+/// assert!(!board.place_tile(&tiles, &options).is_empty());
+///
+/// options.prune_regions = true;
+/// assert!(board.place_tile(&tiles, &options).is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SearchOptions {
+    /// How to choose the position to tile next.  The default is `MaxConstrained`.
+    pub cell_selector: Arc<dyn CellSelector>,
+
+    /// Whether to give up on a board if it has a connected region of unmarked positions whose
+    /// area can't be made up from the areas of our tiles.  This is off by default, since it
+    /// costs a pass over the board for every tile we place down.
+    pub prune_regions: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            cell_selector: Arc::new(MaxConstrained),
+            prune_regions: false,
+        }
+    }
+}
+
+/// Chooses the position to tile next when placing down a tile on a board.
+///
+/// `RectangularBoard::place_tile` returns every way of covering the chosen position, so any
+/// choice gives the same tilings, but the choice makes a big difference to the number of
+/// intermediate boards we visit.
+pub trait CellSelector: fmt::Debug + Send + Sync {
+    /// Returns the `(row, col)` of an unmarked position on the board,
+    /// or None if every position is marked
+    fn select(&self, board: &RectangularBoard, tiles: &TileCollection) -> Option<(usize, usize)>;
+}
+
+/// Chooses the unmarked position with the most sides on the edge of the board or next to a
+/// marked position, scanning the board one column at a time.  This is the default.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MaxConstrained;
+
+impl CellSelector for MaxConstrained {
+    fn select(&self, board: &RectangularBoard, _tiles: &TileCollection) -> Option<(usize, usize)> {
+        let mut largest: Option<(usize, (usize, usize))> = None;
+
+        for col in 0..board.width {
+            for row in 0..board.height {
+                if board.board[row][col] {
+                    continue;
+                }
+
+                let count = board.count((row, col));
+
+                // a position which is surrounded on all sides can't do any better,
+                // and can only be covered by a 1x1 tile
+                if count == 4 {
+                    return Some((row, col));
+                }
+
+                if largest.is_none_or(|(c, _)| count > c) {
+                    largest = Some((count, (row, col)));
+                }
+            }
+        }
+
+        largest.map(|(_, position)| position)
+    }
+}
+
+/// Chooses the first unmarked position in row-major order.
+///
+/// Every board we visit is then filled in up to some position, so different orders of placing
+/// down the same tiles often lead to the same board, which keeps the tiling graph small.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FirstEmpty;
+
+impl CellSelector for FirstEmpty {
+    fn select(&self, board: &RectangularBoard, _tiles: &TileCollection) -> Option<(usize, usize)> {
+        (0..board.height)
+            .flat_map(|row| (0..board.width).map(move |col| (row, col)))
+            .find(|(row, col)| !board.board[*row][*col])
+    }
+}
+
+/// Chooses the unmarked position which can be covered in the fewest ways (the "minimum
+/// remaining values" heuristic).  This keeps the branching factor low, at the cost of trying
+/// every tile at every position.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FewestPlacements;
+
+impl CellSelector for FewestPlacements {
+    fn select(&self, board: &RectangularBoard, tiles: &TileCollection) -> Option<(usize, usize)> {
+        let mut fewest: Option<(usize, (usize, usize))> = None;
+
+        for row in 0..board.height {
+            for col in 0..board.width {
+                if board.board[row][col] {
+                    continue;
+                }
+
+                // there's no need to keep counting once we can't beat the fewest so far
+                let limit = fewest.map_or(usize::MAX, |(p, _)| p);
+                let placements = board.num_placements_covering((row, col), tiles, limit);
+
+                // we can't do better than a position we're forced to cover in one way
+                // (or which can't be covered at all)
+                if placements <= 1 {
+                    return Some((row, col));
+                }
+
+                if fewest.is_none_or(|(p, _)| placements < p) {
+                    fewest = Some((placements, (row, col)));
+                }
+            }
+        }
+
+        fewest.map(|(_, position)| position)
+    }
+}
+
+/// Chooses the first unmarked position on the earliest anti-diagonal (i.e. with the smallest
+/// `row + col`), breaking ties by row.  The tiled part of the board is then roughly a staircase,
+/// whose frontier sweeps diagonally across the board.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Frontier;
+
+impl CellSelector for Frontier {
+    fn select(&self, board: &RectangularBoard, _tiles: &TileCollection) -> Option<(usize, usize)> {
+        (0..board.width + board.height)
+            .flat_map(|diagonal| {
+                (0..board.height.min(diagonal + 1))
+                    .filter(move |row| diagonal - row < board.width)
+                    .map(move |row| (row, diagonal - row))
+            })
+            .find(|(row, col)| !board.board[*row][*col])
+    }
+}
@@ -0,0 +1,202 @@
+use crate::board::RectangularBoard;
+use crate::symmetry::Symmetry;
+use crate::tile::TileCollection;
+use crate::tiling::Tiling;
+
+/// How we decided whether a rectangle can be tiled
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Proof {
+    /// The area of the rectangle isn't a sum of the areas of the tiles
+    Area,
+
+    /// The rectangle is the transpose of a rectangle we've already decided
+    /// (and the tiles are unchanged by swapping rows and columns)
+    Transpose,
+
+    /// The rectangle can be cut into two smaller rectangles which can be tiled
+    Composition,
+
+    /// We searched for a tiling of the rectangle
+    Search,
+}
+
+impl Proof {
+    pub fn name(self) -> &'static str {
+        match self {
+            Proof::Area => "area",
+            Proof::Transpose => "transpose",
+            Proof::Composition => "composition",
+            Proof::Search => "search",
+        }
+    }
+}
+
+/// Whether a rectangle can be tiled, and how we know
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SweepEntry {
+    pub tileable: bool,
+    pub proof: Proof,
+}
+
+/// Decides, for every `width` x `height` rectangle with both sides at most some bound,
+/// whether the rectangle can be tiled by a collection of tiles
+#[derive(Clone, Debug)]
+pub struct RectangleSweep {
+    bound: usize,
+
+    // The entry for each rectangle, indexed by `[height - 1][width - 1]`
+    entries: Vec<Vec<SweepEntry>>,
+}
+
+impl RectangleSweep {
+    /// Decides which rectangles with sides at most `bound` can be tiled by the given tiles,
+    /// ignoring their forbidden placements and positions.
+    ///
+    /// Rectangles are handled in order of height, and then width, and we only search for a
+    /// tiling if the answer doesn't follow from the area of the rectangle or from the
+    /// rectangles we've already handled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::sweep::{Proof, RectangleSweep};
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// // L-trominoes
+    /// let sweep = RectangleSweep::new(&TileCollection::from(Tile::l_tile(2)), 6);
+    ///
+    /// assert!(!sweep.entry(3, 3).tileable);
+    /// assert_eq!(sweep.entry(3, 3).proof, Proof::Search);
+    /// assert_eq!(sweep.entry(4, 4).proof, Proof::Area);
+    ///
+    /// // 2x3 is the transpose of 3x2, and 6x2 is made out of two 3x2 rectangles
+    /// assert!(sweep.entry(2, 3).tileable);
+    /// assert_eq!(sweep.entry(2, 3).proof, Proof::Transpose);
+    /// assert!(sweep.entry(6, 2).tileable);
+    /// assert_eq!(sweep.entry(6, 2).proof, Proof::Composition);
+    /// ```
+    pub fn new(tiles: &TileCollection, bound: usize) -> Self {
+        let tiles = tiles.unrestricted();
+        let transposable = tiles.is_invariant_under(Symmetry::ReflectDiagonal);
+
+        let mut sweep = RectangleSweep {
+            bound,
+            entries: Vec::with_capacity(bound),
+        };
+
+        for height in 1..=bound {
+            let mut row: Vec<SweepEntry> = Vec::with_capacity(bound);
+
+            for width in 1..=bound {
+                let entry = if !tiles.can_cover_area(width * height) {
+                    SweepEntry {
+                        tileable: false,
+                        proof: Proof::Area,
+                    }
+                } else if transposable && width < height {
+                    SweepEntry {
+                        tileable: sweep.entry(height, width).tileable,
+                        proof: Proof::Transpose,
+                    }
+                } else if (1..height).any(|h| {
+                    sweep.entry(width, h).tileable && sweep.entry(width, height - h).tileable
+                }) || (1..width)
+                    .any(|w| row[w - 1].tileable && row[width - w - 1].tileable)
+                {
+                    SweepEntry {
+                        tileable: true,
+                        proof: Proof::Composition,
+                    }
+                } else {
+                    let board = RectangularBoard::new(width, height);
+
+                    SweepEntry {
+                        tileable: Tiling::search(&board, tiles).is_some(),
+                        proof: Proof::Search,
+                    }
+                };
+
+                row.push(entry);
+            }
+
+            sweep.entries.push(row);
+        }
+
+        sweep
+    }
+
+    pub fn bound(&self) -> usize {
+        self.bound
+    }
+
+    /// Returns the entry for the `width` x `height` rectangle
+    ///
+    /// # Panics
+    ///
+    /// Will panic if either side is zero, or larger than the bound of the sweep.
+    pub fn entry(&self, width: usize, height: usize) -> SweepEntry {
+        self.entries[height - 1][width - 1]
+    }
+
+    /// Returns the results as CSV, with a line `width,height,tileable,proof` for each rectangle
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("width,height,tileable,proof\n");
+
+        for height in 1..=self.bound {
+            for width in 1..=self.bound {
+                let entry = self.entry(width, height);
+                csv += &format!(
+                    "{},{},{},{}\n",
+                    width,
+                    height,
+                    entry.tileable,
+                    entry.proof.name()
+                );
+            }
+        }
+
+        csv
+    }
+
+    /// Returns the results as a table with a row for each height and a column for each width.
+    /// Each entry is the first letter of its proof, which is uppercase if the rectangle can be
+    /// tiled and lowercase otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::sweep::RectangleSweep;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let sweep = RectangleSweep::new(&TileCollection::from(Tile::l_tile(1)), 3);
+    /// assert_eq!(sweep.to_table(), "   1 2 3\n1  a S a\n2  T C C\n3  a T a\n");
+    /// ```
+    pub fn to_table(&self) -> String {
+        let digits = self.bound.to_string().len();
+
+        let mut table = format!("{:digits$} ", "");
+        for width in 1..=self.bound {
+            table += &format!(" {:>digits$}", width);
+        }
+        table.push('\n');
+
+        for height in 1..=self.bound {
+            table += &format!("{:>digits$} ", height);
+
+            for width in 1..=self.bound {
+                let entry = self.entry(width, height);
+                let letter = entry.proof.name().chars().next().unwrap();
+                let letter = if entry.tileable {
+                    letter.to_ascii_uppercase()
+                } else {
+                    letter
+                };
+
+                table += &format!(" {:>digits$}", letter);
+            }
+            table.push('\n');
+        }
+
+        table
+    }
+}
@@ -0,0 +1,85 @@
+/// An element of the symmetry group of a square (the dihedral group of order 8).
+///
+/// Cells are indexed by `(row, col)`, with rows increasing downwards.  The symmetries
+/// `Rotate90`, `Rotate270`, `ReflectDiagonal` and `ReflectAntiDiagonal` swap the roles
+/// of rows and columns, so they only map a board to itself if the board is square.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    ReflectHorizontal,
+    ReflectVertical,
+    ReflectDiagonal,
+    ReflectAntiDiagonal,
+}
+
+impl Symmetry {
+    /// Returns all eight symmetries of the square
+    pub fn all() -> [Symmetry; 8] {
+        [
+            Symmetry::Identity,
+            Symmetry::Rotate90,
+            Symmetry::Rotate180,
+            Symmetry::Rotate270,
+            Symmetry::ReflectHorizontal,
+            Symmetry::ReflectVertical,
+            Symmetry::ReflectDiagonal,
+            Symmetry::ReflectAntiDiagonal,
+        ]
+    }
+
+    /// Determines whether this symmetry swaps rows and columns
+    pub fn requires_square(self) -> bool {
+        matches!(
+            self,
+            Symmetry::Rotate90
+                | Symmetry::Rotate270
+                | Symmetry::ReflectDiagonal
+                | Symmetry::ReflectAntiDiagonal
+        )
+    }
+
+    /// Applies this symmetry to the cell `(row, col)` of a `width` x `height` board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::symmetry::Symmetry;
+    ///
+    /// // rotating clockwise moves the top left corner to the top right corner
+    /// assert_eq!(Symmetry::Rotate90.apply((0, 0), 3, 3), (0, 2));
+    /// assert_eq!(Symmetry::ReflectHorizontal.apply((0, 1), 4, 2), (1, 1));
+    /// ```
+    pub fn apply(self, cell: (usize, usize), width: usize, height: usize) -> (usize, usize) {
+        let (r, c) = cell;
+
+        match self {
+            Symmetry::Identity => (r, c),
+            Symmetry::Rotate90 => (c, height - 1 - r),
+            Symmetry::Rotate180 => (height - 1 - r, width - 1 - c),
+            Symmetry::Rotate270 => (width - 1 - c, r),
+            Symmetry::ReflectHorizontal => (height - 1 - r, c),
+            Symmetry::ReflectVertical => (r, width - 1 - c),
+            Symmetry::ReflectDiagonal => (c, r),
+            Symmetry::ReflectAntiDiagonal => (width - 1 - c, height - 1 - r),
+        }
+    }
+
+    /// Applies this symmetry to a `(row, col)` offset between two cells
+    pub fn apply_offset(self, offset: (isize, isize)) -> (isize, isize) {
+        let (dr, dc) = offset;
+
+        match self {
+            Symmetry::Identity => (dr, dc),
+            Symmetry::Rotate90 => (dc, -dr),
+            Symmetry::Rotate180 => (-dr, -dc),
+            Symmetry::Rotate270 => (-dc, dr),
+            Symmetry::ReflectHorizontal => (-dr, dc),
+            Symmetry::ReflectVertical => (dr, -dc),
+            Symmetry::ReflectDiagonal => (dc, dr),
+            Symmetry::ReflectAntiDiagonal => (-dc, -dr),
+        }
+    }
+}
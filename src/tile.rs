@@ -1,4 +1,9 @@
-use std::collections::HashSet;
+use crate::placement::PlacementTable;
+use crate::symmetry::Symmetry;
+use crate::topology::Topology;
+use num::Integer;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
@@ -39,6 +44,20 @@ impl Direction {
         }
     }
 
+    /// Returns the `(row, col)` offset obtained by moving one step in this direction
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (-1, 1),
+            Direction::DownLeft => (1, -1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+
     pub fn rotate(self) -> Self {
         match self {
             Direction::Up => Direction::Right,
@@ -165,64 +184,479 @@ impl Tile {
     pub fn reflect(&self, axis: Axis) -> Tile {
         Tile::new(self.directions.iter().map(|d| d.reflect(axis)).collect())
     }
+
+    /// Returns the `(row, col)` offsets of the blocks making up this tile, translated
+    /// so that the smallest row and column are both zero.  The result is sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dcc_tiler::tile::Tile;
+    ///
+    /// let tile = Tile::l_tile(2);
+    /// assert_eq!(tile.cells(), vec![(0, 0), (1, 0), (1, 1)]);
+    /// ```
+    pub fn cells(&self) -> Vec<(isize, isize)> {
+        let mut current = (0, 0);
+        let mut cells = vec![current];
+
+        for direction in &self.directions {
+            let (dr, dc) = direction.offset();
+            current = (current.0 + dr, current.1 + dc);
+            cells.push(current);
+        }
+
+        normalize_cells(cells)
+    }
 }
 
+/// Translates a collection of cells so that the smallest row and column are both zero,
+/// then sorts and deduplicates them
+fn normalize_cells(mut cells: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
+    let min_row = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let min_col = cells.iter().map(|c| c.1).min().unwrap_or(0);
+
+    for cell in cells.iter_mut() {
+        *cell = (cell.0 - min_row, cell.1 - min_col);
+    }
+
+    cells.sort_unstable();
+    cells.dedup();
+    cells
+}
+
+/// Converts a collection of `(row, col)` board positions into cells of the form returned by `Tile::cells`
+///
+/// # Examples
+///
+/// ```rust
+/// use dcc_tiler::tile::{normalize_positions, Tile};
+///
+/// assert_eq!(normalize_positions(&[(4, 3), (3, 2), (4, 2)]), Tile::l_tile(2).cells());
+/// ```
+pub fn normalize_positions(positions: &[(usize, usize)]) -> Vec<(isize, isize)> {
+    normalize_cells(
+        positions
+            .iter()
+            .map(|(r, c)| (*r as isize, *c as isize))
+            .collect(),
+    )
+}
+
+/// Placement tables indexed by the `(width, height)` and topology of the board
+type PlacementTables = HashMap<(usize, usize, Topology), Arc<PlacementTable>>;
+
 #[derive(Debug, Clone)]
 pub struct TileCollection {
     tiles: Vec<Tile>,
+    // The shape that each tile belongs to (as an index into the list of shapes)
+    shapes: Vec<usize>,
     contains_single_tile: bool,
+
+    // Placements (as sorted lists of (row, col) board positions) which may not be used
+    forbidden_placements: HashSet<Vec<(usize, usize)>>,
+
+    // For each shape, the board positions which may not be covered by a tile of that shape
+    forbidden_positions: HashMap<usize, HashSet<(usize, usize)>>,
+
+    // A copy of this collection without anything forbidden, made when we first forbid something
+    unrestricted: Option<Arc<TileCollection>>,
+
+    // The placement table for each `(width, height)` of board we've seen, computed on demand
+    placement_tables: Arc<RwLock<PlacementTables>>,
+
+    // Whether each area smaller than the square of the largest tile area is a sum of tile areas
+    coverable_areas: Vec<bool>,
+
+    // The gcd of the tile areas; every larger area is coverable exactly when it's a multiple of this
+    area_gcd: usize,
 }
 
 impl TileCollection {
+    /// Creates a new tile collection, where all of the tiles are considered to have the same shape
     pub fn new(tiles: Vec<Tile>) -> Self {
+        let shapes = vec![0; tiles.len()];
+        let (coverable_areas, area_gcd) = coverable_areas(&tiles);
+
         TileCollection {
             contains_single_tile: tiles.iter().any(|b| b.directions.is_empty()),
             tiles,
+            shapes,
+            forbidden_placements: HashSet::new(),
+            forbidden_positions: HashMap::new(),
+            unrestricted: None,
+            placement_tables: Default::default(),
+            coverable_areas,
+            area_gcd,
+        }
+    }
+
+    /// Creates a tile collection consisting of the orbits of each of the given tiles
+    /// under rotations and reflections.  The orbit of the `i`-th tile makes up the `i`-th shape.
+    ///
+    /// The tiles should all have different shapes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let tiles = TileCollection::from_shapes(vec![Tile::l_tile(2), Tile::box_tile()]);
+    /// assert_eq!(tiles.num_shapes(), 2);
+    /// assert!(tiles.contains_single_tile());
+    /// ```
+    pub fn from_shapes(shapes: Vec<Tile>) -> Self {
+        let mut collection = TileCollection::new(Vec::new());
+
+        for (shape, tile) in shapes.into_iter().enumerate() {
+            for tile in symmetry_orbit(tile) {
+                collection.contains_single_tile |= tile.directions.is_empty();
+                collection.tiles.push(tile);
+                collection.shapes.push(shape);
+            }
         }
+
+        let (coverable_areas, area_gcd) = coverable_areas(&collection.tiles);
+        collection.coverable_areas = coverable_areas;
+        collection.area_gcd = area_gcd;
+
+        collection
+    }
+
+    /// Returns the number of distinct shapes in this collection
+    pub fn num_shapes(&self) -> usize {
+        self.shapes.iter().max().map_or(0, |s| s + 1)
     }
 
     pub fn contains_single_tile(&self) -> bool {
         self.contains_single_tile
     }
 
-    pub fn iter<'b>(&'b self) -> Box<dyn Iterator<Item = &Tile> + 'b> {
+    /// Returns the placement table for a board of the given size and topology, computing it
+    /// the first time it's needed
+    pub fn placement_table(
+        &self,
+        width: usize,
+        height: usize,
+        topology: Topology,
+    ) -> Arc<PlacementTable> {
+        let key = (width, height, topology);
+
+        if let Some(table) = self.placement_tables.read().unwrap().get(&key) {
+            return table.clone();
+        }
+
+        let table = Arc::new(PlacementTable::new(width, height, topology, self));
+
+        self.placement_tables
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert(table)
+            .clone()
+    }
+
+    /// Determines whether a region with the given number of positions could be covered by
+    /// our tiles, judging by their areas alone
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let tiles = TileCollection::from_shapes(vec![Tile::l_tile(2), Tile::t_tile(1)]);
+    /// assert!(tiles.can_cover_area(7));
+    /// assert!(!tiles.can_cover_area(5));
+    /// ```
+    pub fn can_cover_area(&self, area: usize) -> bool {
+        match self.coverable_areas.get(area) {
+            Some(coverable) => *coverable,
+            None => area.is_multiple_of(self.area_gcd),
+        }
+    }
+
+    /// Returns the shape of the tile covering the given `(row, col)` positions,
+    /// or None if no tile in this collection has that shape
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let tiles = TileCollection::from_shapes(vec![Tile::l_tile(1), Tile::box_tile()]);
+    /// assert_eq!(tiles.shape_of(&[(3, 2), (4, 2)]), Some(0));
+    /// assert_eq!(tiles.shape_of(&[(1, 1)]), Some(1));
+    /// assert_eq!(tiles.shape_of(&[(0, 0), (1, 1)]), None);
+    /// ```
+    pub fn shape_of(&self, positions: &[(usize, usize)]) -> Option<usize> {
+        let cells = normalize_positions(positions);
+
+        self.iter_with_shapes()
+            .find(|(_, tile)| tile.cells() == cells)
+            .map(|(shape, _)| shape)
+    }
+
+    /// Returns the distinct orientations of the tiles in this collection, each given by
+    /// the `(row, col)` offsets of its blocks (as for `Tile::cells`)
+    pub fn orientations(&self) -> Vec<Vec<(isize, isize)>> {
+        let mut orientations = Vec::new();
+
+        for tile in self.tiles.iter() {
+            let cells = tile.cells();
+
+            if !orientations.contains(&cells) {
+                orientations.push(cells);
+            }
+        }
+
+        orientations
+    }
+
+    /// Returns the index (into `orientations`) of the orientation of the tile covering the
+    /// given `(row, col)` positions, or None if no tile in this collection has that orientation
+    pub fn orientation_of(&self, positions: &[(usize, usize)]) -> Option<usize> {
+        let cells = normalize_positions(positions);
+
+        self.orientations().iter().position(|o| *o == cells)
+    }
+
+    /// Forbids placing a tile covering exactly the given `(row, col)` board positions
+    pub fn forbid_placement(&mut self, positions: &[(usize, usize)]) {
+        let mut positions = positions.to_vec();
+        positions.sort_unstable();
+        positions.dedup();
+
+        self.forbidden_placements.insert(positions);
+        self.placement_tables = Default::default();
+        self.cache_unrestricted();
+    }
+
+    /// Forbids tiles of the given shape from covering the given `(row, col)` board position
+    pub fn forbid_position(&mut self, shape: usize, position: (usize, usize)) {
+        self.forbidden_positions
+            .entry(shape)
+            .or_default()
+            .insert(position);
+        self.placement_tables = Default::default();
+        self.cache_unrestricted();
+    }
+
+    fn cache_unrestricted(&mut self) {
+        if self.unrestricted.is_none() {
+            self.unrestricted = Some(Arc::new(self.without_forbidden()));
+        }
+    }
+
+    /// Determines whether any placements have been forbidden
+    pub fn has_forbidden(&self) -> bool {
+        !self.forbidden_placements.is_empty() || !self.forbidden_positions.is_empty()
+    }
+
+    /// Determines whether a tile of the given shape may be placed covering
+    /// the given `(row, col)` board positions
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let mut tiles = TileCollection::from(Tile::l_tile(1));
+    /// tiles.forbid_placement(&[(0, 1), (0, 0)]);
+    /// tiles.forbid_position(0, (2, 2));
+    ///
+    /// assert!(!tiles.allows(0, &[(0, 0), (0, 1)]));
+    /// assert!(!tiles.allows(0, &[(2, 2), (2, 3)]));
+    /// assert!(tiles.allows(0, &[(0, 0), (1, 0)]));
+    /// ```
+    pub fn allows(&self, shape: usize, positions: &[(usize, usize)]) -> bool {
+        if let Some(forbidden) = self.forbidden_positions.get(&shape) {
+            if positions.iter().any(|p| forbidden.contains(p)) {
+                return false;
+            }
+        }
+
+        if self.forbidden_placements.is_empty() {
+            return true;
+        }
+
+        let mut positions = positions.to_vec();
+        positions.sort_unstable();
+        positions.dedup();
+
+        !self.forbidden_placements.contains(&positions)
+    }
+
+    /// Returns this collection without any forbidden placements or positions.  Unlike
+    /// `without_forbidden`, this doesn't make a copy, so the placement tables it computes
+    /// are kept from one call to the next.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let mut tiles = TileCollection::from(Tile::l_tile(1));
+    /// tiles.forbid_placement(&[(0, 0), (0, 1)]);
+    ///
+    /// assert!(!tiles.unrestricted().has_forbidden());
+    /// assert!(std::ptr::eq(tiles.unrestricted(), tiles.unrestricted()));
+    /// ```
+    pub fn unrestricted(&self) -> &TileCollection {
+        self.unrestricted.as_deref().unwrap_or(self)
+    }
+
+    /// Returns a copy of this collection without any forbidden placements or positions
+    pub fn without_forbidden(&self) -> TileCollection {
+        TileCollection {
+            tiles: self.tiles.clone(),
+            shapes: self.shapes.clone(),
+            contains_single_tile: self.contains_single_tile,
+            forbidden_placements: HashSet::new(),
+            forbidden_positions: HashMap::new(),
+            unrestricted: None,
+            placement_tables: Default::default(),
+            coverable_areas: self.coverable_areas.clone(),
+            area_gcd: self.area_gcd,
+        }
+    }
+
+    /// Determines whether the forbidden placements and positions of this collection are mapped
+    /// to one another by the given symmetry of a `width` x `height` board.  Forbidden placements
+    /// and positions which don't lie on the board can never be used, so they're ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dcc_tiler::symmetry::Symmetry;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// let mut tiles = TileCollection::from(Tile::l_tile(1));
+    /// tiles.forbid_position(0, (10, 10));
+    /// assert!(tiles.forbidden_invariant_under(Symmetry::Rotate90, 4, 4));
+    ///
+    /// tiles.forbid_position(0, (0, 0));
+    /// assert!(!tiles.forbidden_invariant_under(Symmetry::Rotate90, 4, 4));
+    /// ```
+    pub fn forbidden_invariant_under(
+        &self,
+        symmetry: Symmetry,
+        width: usize,
+        height: usize,
+    ) -> bool {
+        let on_board = |(row, col): &(usize, usize)| *row < height && *col < width;
+
+        let placements_invariant = self
+            .forbidden_placements
+            .iter()
+            .filter(|placement| placement.iter().all(on_board))
+            .all(|placement| {
+                let mut image: Vec<_> = placement
+                    .iter()
+                    .map(|p| symmetry.apply(*p, width, height))
+                    .collect();
+                image.sort_unstable();
+
+                self.forbidden_placements.contains(&image)
+            });
+
+        let positions_invariant = self.forbidden_positions.values().all(|positions| {
+            positions
+                .iter()
+                .filter(|p| on_board(p))
+                .all(|p| positions.contains(&symmetry.apply(*p, width, height)))
+        });
+
+        placements_invariant && positions_invariant
+    }
+
+    /// Determines whether applying the given symmetry to every tile in this collection
+    /// results in a tile whose shape already appears in the collection
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dcc_tiler::symmetry::Symmetry;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    ///
+    /// // a single L-tile is not invariant under rotation, but its orbit is
+    /// let tile = Tile::l_tile(2);
+    /// assert!(!TileCollection::new(vec![tile.clone()]).is_invariant_under(Symmetry::Rotate90));
+    /// assert!(TileCollection::from(tile).is_invariant_under(Symmetry::Rotate90));
+    /// ```
+    pub fn is_invariant_under(&self, symmetry: Symmetry) -> bool {
+        let shapes: HashSet<_> = self.tiles.iter().map(|t| t.cells()).collect();
+
+        shapes.iter().all(|shape| {
+            let image = shape.iter().map(|c| symmetry.apply_offset(*c)).collect();
+            shapes.contains(&normalize_cells(image))
+        })
+    }
+
+    pub fn iter<'b>(&'b self) -> Box<dyn Iterator<Item = &'b Tile> + 'b> {
         Box::new(self.tiles.iter())
     }
+
+    /// Iterates over the tiles in this collection, along with the shape each tile belongs to
+    pub fn iter_with_shapes<'b>(&'b self) -> Box<dyn Iterator<Item = (usize, &'b Tile)> + 'b> {
+        Box::new(self.shapes.iter().copied().zip(self.tiles.iter()))
+    }
 }
 
 impl From<Tile> for TileCollection {
     fn from(tile: Tile) -> Self {
-        /// Generates the orbit of this tile under the symmetry + rotate actions
-        fn symmetry_orbit(tile: Tile) -> TileCollection {
-            let mut orbit = HashSet::new();
+        TileCollection::from_shapes(vec![tile])
+    }
+}
 
-            // our starting set of directions
-            orbit.insert(tile);
+/// Generates the orbit of this tile under the symmetry + rotate actions
+fn symmetry_orbit(tile: Tile) -> Vec<Tile> {
+    let mut orbit = HashSet::new();
 
-            loop {
-                // in each iteration, we check whether our directions set
-                // increased.  If it didn't, then we've got the entire orbit
-                let current_size = orbit.len();
+    // our starting set of directions
+    orbit.insert(tile);
 
-                let mut to_insert = Vec::new();
+    loop {
+        // in each iteration, we check whether our directions set
+        // increased.  If it didn't, then we've got the entire orbit
+        let current_size = orbit.len();
 
-                for directions in &orbit {
-                    // apply the rotate function
-                    to_insert.push(directions.rotate());
-                    // apply the two axis reflections
-                    to_insert.push(directions.reflect(Axis::Horizontal));
-                    to_insert.push(directions.reflect(Axis::Vertical));
-                }
+        let mut to_insert = Vec::new();
 
-                orbit.extend(to_insert);
+        for directions in &orbit {
+            // apply the rotate function
+            to_insert.push(directions.rotate());
+            // apply the two axis reflections
+            to_insert.push(directions.reflect(Axis::Horizontal));
+            to_insert.push(directions.reflect(Axis::Vertical));
+        }
 
-                if orbit.len() == current_size {
-                    break;
-                }
-            }
+        orbit.extend(to_insert);
 
-            TileCollection::new(orbit.into_iter().collect())
+        if orbit.len() == current_size {
+            break;
         }
-        symmetry_orbit(tile)
     }
+
+    orbit.into_iter().collect()
+}
+
+/// Determines which areas smaller than the square of the largest tile area are sums of tile
+/// areas, along with the gcd of the tile areas.  Every larger multiple of the gcd is such a sum,
+/// since the Frobenius number of the tile areas is smaller than the square of the largest.
+fn coverable_areas(tiles: &[Tile]) -> (Vec<bool>, usize) {
+    let mut areas: Vec<usize> = tiles.iter().map(|tile| tile.cells().len()).collect();
+    areas.sort_unstable();
+    areas.dedup();
+
+    let largest = areas.last().copied().unwrap_or(0);
+    let gcd = areas.iter().fold(0, |gcd, area| area.gcd(&gcd));
+
+    let mut coverable = vec![false; (largest * largest).max(1)];
+    coverable[0] = true;
+
+    for a in 1..coverable.len() {
+        coverable[a] = areas.iter().any(|t| *t <= a && coverable[a - t]);
+    }
+
+    (coverable, gcd)
 }
@@ -0,0 +1,315 @@
+use crate::board::RectangularBoard;
+use crate::selector::SearchOptions;
+use crate::tile::TileCollection;
+use std::collections::{BTreeMap, HashSet};
+
+/// A complete tiling of a board.
+///
+/// We store, for each position, the first position (in row-major order) of the tile covering it.
+/// This representation doesn't depend on the order the tiles were placed down in, so two tilings
+/// are equal exactly when they consist of the same tiles.  It also lets us replace a few tiles
+/// without touching the rest of the board.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tiling {
+    pub width: usize,
+    pub height: usize,
+
+    // The first position of the tile covering each position, or None
+    // if the position was already marked on the initial board
+    anchors: Vec<Vec<Option<(usize, usize)>>>,
+}
+
+impl Tiling {
+    /// Creates a tiling from a sequence of boards, starting at the initial board, where each
+    /// board is obtained from the previous one by placing down a tile (as returned by, e.g.,
+    /// `Tiler::get_single_tiling`).  The last board should be completely marked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tiling::Tiling;
+    ///
+    /// let board = RectangularBoard::new(2, 2);
+    /// let first = board.place_tile_at(&[(0, 0), (1, 0)]).unwrap();
+    /// let second = first.place_tile_at(&[(0, 1), (1, 1)]).unwrap();
+    /// let tiling = Tiling::from_boards(&[board, first, second]);
+    ///
+    /// assert_eq!(tiling.tiles(), vec![vec![(0, 0), (1, 0)], vec![(0, 1), (1, 1)]]);
+    /// assert_eq!(tiling.tile_at((1, 1)), Some(vec![(0, 1), (1, 1)]));
+    /// ```
+    pub fn from_boards(boards: &[RectangularBoard]) -> Self {
+        let initial_board = &boards[0];
+        let mut anchors = vec![vec![None; initial_board.width]; initial_board.height];
+
+        for pair in boards.windows(2) {
+            let positions = pair[0].changed_positions(&pair[1]);
+
+            // changed_positions returns positions in row-major order
+            for (row, col) in positions.iter() {
+                anchors[*row][*col] = Some(positions[0]);
+            }
+        }
+
+        Tiling {
+            width: initial_board.width,
+            height: initial_board.height,
+            anchors,
+        }
+    }
+
+    /// Searches for a tiling of the given board, placing down tiles one at a time and backtracking
+    /// whenever we get stuck.  Returns None if the board can't be tiled.
+    ///
+    /// Unlike `Tiler::get_single_tiling`, we only keep a single copy of the board around,
+    /// so this can be used for very large boards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiling::Tiling;
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    ///
+    /// let tiling = Tiling::search(&RectangularBoard::new(40, 30), &tiles).unwrap();
+    /// assert_eq!(tiling.tiles().len(), 600);
+    /// assert!(Tiling::search(&RectangularBoard::new(3, 3), &tiles).is_none());
+    /// ```
+    pub fn search(board: &RectangularBoard, tiles: &TileCollection) -> Option<Self> {
+        let mut board = board.clone();
+        let mut tiling = Tiling {
+            width: board.width,
+            height: board.height,
+            anchors: vec![vec![None; board.width]; board.height],
+        };
+
+        // the tiles we've placed down so far, and for each of them (and the next tile), the
+        // placements we haven't tried yet
+        let options = SearchOptions::default();
+        let mut placed: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut untried = vec![board.next_placements(tiles, &options)];
+
+        while !board.is_all_marked() {
+            match untried.last_mut()?.pop() {
+                Some(tile) => {
+                    board.mark_positions(&tile);
+                    placed.push(tile);
+                    untried.push(board.next_placements(tiles, &options));
+                }
+                None => {
+                    // we've tried everything here, so backtrack
+                    untried.pop();
+
+                    if let Some(tile) = placed.pop() {
+                        board.unmark_positions(&tile);
+                    }
+                }
+            }
+        }
+
+        tiling.retile(&placed);
+        Some(tiling)
+    }
+
+    /// Returns the sequence of boards obtained by placing down the tiles of this tiling
+    /// one at a time, in the format expected by `render_single_tiling_from_vec`
+    pub fn to_boards(&self) -> Vec<RectangularBoard> {
+        let mut boards = vec![self.initial_board()];
+
+        for tile in self.tiles() {
+            let board = boards.last().unwrap().place_tile_at(&tile).unwrap();
+            boards.push(board);
+        }
+
+        boards
+    }
+
+    /// Returns the board being tiled, before any tiles have been placed down
+    pub fn initial_board(&self) -> RectangularBoard {
+        RectangularBoard::from_marks(
+            self.anchors
+                .iter()
+                .map(|row| row.iter().map(|a| a.is_none()).collect())
+                .collect(),
+        )
+    }
+
+    /// Returns whether the given `(row, col)` position is part of the board being tiled
+    pub fn is_covered(&self, position: (usize, usize)) -> bool {
+        self.anchors[position.0][position.1].is_some()
+    }
+
+    /// Returns whether the given `(row, col)` positions are covered by the same tile
+    pub fn same_tile(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let anchor = self.anchors[a.0][a.1];
+        anchor.is_some() && anchor == self.anchors[b.0][b.1]
+    }
+
+    /// Returns the tiles making up this tiling, each given by its `(row, col)` positions in
+    /// row-major order.  The tiles are ordered by their first position.
+    pub fn tiles(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut tiles: BTreeMap<(usize, usize), Vec<(usize, usize)>> = BTreeMap::new();
+
+        for (row, anchors) in self.anchors.iter().enumerate() {
+            for (col, anchor) in anchors.iter().enumerate() {
+                if let Some(anchor) = anchor {
+                    tiles.entry(*anchor).or_default().push((row, col));
+                }
+            }
+        }
+
+        tiles.into_values().collect()
+    }
+
+    /// Returns the positions (in row-major order) of the tile covering the given
+    /// position, or None if the position isn't part of the board
+    pub fn tile_at(&self, position: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        let anchor = self.anchors[position.0][position.1]?;
+
+        // Consecutive blocks of a tile are adjacent (possibly diagonally), so we can find
+        // all the positions of the tile by searching outwards from the anchor
+        let mut positions = vec![anchor];
+        let mut seen = HashSet::new();
+        seen.insert(anchor);
+        let mut next = 0;
+
+        while next < positions.len() {
+            let (row, col) = positions[next];
+
+            for r in row.saturating_sub(1)..(row + 2).min(self.height) {
+                for c in col.saturating_sub(1)..(col + 2).min(self.width) {
+                    if self.anchors[r][c] == Some(anchor) && seen.insert((r, c)) {
+                        positions.push((r, c));
+                    }
+                }
+            }
+
+            next += 1;
+        }
+
+        positions.sort_unstable();
+        Some(positions)
+    }
+
+    /// Returns all the ways of re-tiling the part of the board covered by those tiles which
+    /// lie entirely inside a window with the given top left `(row, col)` position and size
+    /// `(rows, cols)`.  The current tiles are included, so this is empty only if no tile lies inside
+    /// the window.  Each way of re-tiling is given by a list of tiles, which can be passed to `retile`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::board::RectangularBoard;
+    /// use dcc_tiler::tile::{Tile, TileCollection};
+    /// use dcc_tiler::tiling::Tiling;
+    ///
+    /// // two vertical dominoes
+    /// let board = RectangularBoard::new(2, 2);
+    /// let first = board.place_tile_at(&[(0, 0), (1, 0)]).unwrap();
+    /// let second = first.place_tile_at(&[(0, 1), (1, 1)]).unwrap();
+    /// let mut tiling = Tiling::from_boards(&[board, first, second]);
+    ///
+    /// let tiles = TileCollection::from(Tile::l_tile(1));
+    /// assert_eq!(tiling.window_retilings(&tiles, (0, 0), (2, 1)).len(), 1);
+    ///
+    /// // flip the dominoes
+    /// let retilings = tiling.window_retilings(&tiles, (0, 0), (2, 2));
+    /// assert_eq!(retilings.len(), 2);
+    ///
+    /// let flipped = retilings.iter().find(|t| t[0] == vec![(0, 0), (0, 1)]).unwrap();
+    /// tiling.retile(flipped);
+    /// assert_eq!(tiling.tiles(), vec![vec![(0, 0), (0, 1)], vec![(1, 0), (1, 1)]]);
+    /// ```
+    pub fn window_retilings(
+        &self,
+        tiles: &TileCollection,
+        top_left: (usize, usize),
+        size: (usize, usize),
+    ) -> Vec<Vec<Vec<(usize, usize)>>> {
+        let (top, left) = top_left;
+        let bottom = (top + size.0).min(self.height);
+        let right = (left + size.1).min(self.width);
+
+        if top >= bottom || left >= right {
+            return Vec::new();
+        }
+
+        let in_window =
+            |(row, col): (usize, usize)| row >= top && row < bottom && col >= left && col < right;
+
+        // find the positions inside the window covered by tiles lying entirely inside the window
+        let mut marks = vec![vec![true; right - left]; bottom - top];
+        let mut seen_anchors = HashSet::new();
+        let mut empty = true;
+
+        for row in top..bottom {
+            for col in left..right {
+                if let Some(anchor) = self.anchors[row][col] {
+                    if !seen_anchors.insert(anchor) {
+                        continue;
+                    }
+
+                    let tile = self.tile_at((row, col)).unwrap();
+
+                    if tile.iter().all(|p| in_window(*p)) {
+                        for (r, c) in tile {
+                            marks[r - top][c - left] = false;
+                        }
+                        empty = false;
+                    }
+                }
+            }
+        }
+
+        if empty {
+            return Vec::new();
+        }
+
+        // Now tile this region as a board in its own right.  Forbidden placements refer to
+        // positions on the whole board, so we check those separately.
+        let unrestricted_tiles = tiles.unrestricted();
+        let options = SearchOptions::default();
+        let mut retilings = Vec::new();
+        let mut stack = vec![(RectangularBoard::from_marks(marks), Vec::new())];
+
+        while let Some((board, placed)) = stack.pop() {
+            for (shape, child_board) in board.place_tile_with_shapes(unrestricted_tiles, &options) {
+                let tile: Vec<_> = board
+                    .changed_positions(&child_board)
+                    .into_iter()
+                    .map(|(r, c)| (r + top, c + left))
+                    .collect();
+
+                if tiles.has_forbidden() && !tiles.allows(shape, &tile) {
+                    continue;
+                }
+
+                let mut placed = placed.clone();
+                placed.push(tile);
+
+                if child_board.is_all_marked() {
+                    retilings.push(placed);
+                } else {
+                    stack.push((child_board, placed));
+                }
+            }
+        }
+
+        retilings
+    }
+
+    /// Replaces the tiles covering some part of the board with the given tiles.  The given tiles
+    /// should exactly cover a union of tiles in this tiling, e.g. they could be returned by
+    /// `window_retilings`.
+    pub fn retile(&mut self, tiles: &[Vec<(usize, usize)>]) {
+        for tile in tiles {
+            let anchor = *tile.iter().min().unwrap();
+
+            for (row, col) in tile {
+                self.anchors[*row][*col] = Some(anchor);
+            }
+        }
+    }
+}
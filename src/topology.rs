@@ -0,0 +1,118 @@
+use crate::symmetry::Symmetry;
+use serde_derive::{Deserialize, Serialize};
+
+/// How the edges of a rectangular board are glued together.
+///
+/// Gluing the left and right edges gives a cylinder, or a Möbius strip if the board is flipped
+/// over first (so that row `r` on the right is glued to row `height - 1 - r` on the left).  Also
+/// gluing the top and bottom edges gives a torus or a Klein bottle respectively.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Topology {
+    #[default]
+    Plane,
+    Cylinder,
+    Torus,
+    Mobius,
+    Klein,
+}
+
+impl Topology {
+    /// Returns all the topologies, in the order used by `index`
+    pub fn all() -> [Topology; 5] {
+        [
+            Topology::Plane,
+            Topology::Cylinder,
+            Topology::Torus,
+            Topology::Mobius,
+            Topology::Klein,
+        ]
+    }
+
+    /// Returns the position of this topology in `Topology::all`
+    pub fn index(self) -> usize {
+        Topology::all().iter().position(|t| *t == self).unwrap()
+    }
+
+    pub fn is_plane(&self) -> bool {
+        *self == Topology::Plane
+    }
+
+    /// Determines whether the left and right edges are glued together
+    pub fn wraps_columns(self) -> bool {
+        self != Topology::Plane
+    }
+
+    /// Determines whether the top and bottom edges are glued together
+    pub fn wraps_rows(self) -> bool {
+        matches!(self, Topology::Torus | Topology::Klein)
+    }
+
+    /// Determines whether the board is flipped over when we cross the left or right edge
+    pub fn flips(self) -> bool {
+        matches!(self, Topology::Mobius | Topology::Klein)
+    }
+
+    /// Maps a `(row, col)` position, which may lie outside a `width` x `height` board,
+    /// to the position on the board it is glued to.  Returns None if the position is
+    /// off the edge of the board, which is always the case if the board is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::topology::Topology;
+    ///
+    /// assert_eq!(Topology::Plane.wrap((0, 3), 3, 2), None);
+    /// assert_eq!(Topology::Cylinder.wrap((0, 3), 3, 2), Some((0, 0)));
+    /// assert_eq!(Topology::Cylinder.wrap((2, 0), 3, 2), None);
+    /// assert_eq!(Topology::Mobius.wrap((0, -1), 3, 2), Some((1, 2)));
+    /// assert_eq!(Topology::Klein.wrap((-1, 3), 3, 2), Some((0, 0)));
+    /// assert_eq!(Topology::Torus.wrap((0, 0), 0, 2), None);
+    /// ```
+    pub fn wrap(
+        self,
+        (row, col): (isize, isize),
+        width: usize,
+        height: usize,
+    ) -> Option<(usize, usize)> {
+        // there's nothing to glue together on a board without any positions
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let (width, height) = (width as isize, height as isize);
+        let (mut row, mut col) = (row, col);
+
+        if col < 0 || col >= width {
+            if !self.wraps_columns() {
+                return None;
+            }
+
+            // each time we go around, the board gets flipped over
+            if self.flips() && col.div_euclid(width) % 2 != 0 {
+                row = height - 1 - row;
+            }
+            col = col.rem_euclid(width);
+        }
+
+        if row < 0 || row >= height {
+            if !self.wraps_rows() {
+                return None;
+            }
+
+            row = row.rem_euclid(height);
+        }
+
+        Some((row as usize, col as usize))
+    }
+
+    /// Determines whether the given symmetry of the rectangle respects the gluing of its edges
+    pub fn preserved_by(self, symmetry: Symmetry) -> bool {
+        match self {
+            Topology::Plane | Topology::Torus => true,
+
+            // swapping rows and columns would swap the glued edges with the unglued ones
+            // (or with the edges which are glued without flipping)
+            _ => !symmetry.requires_square(),
+        }
+    }
+}
@@ -0,0 +1,411 @@
+//! Tilings of regions of the triangular grid by polyiamonds.
+//!
+//! Triangular cells are indexed by `(row, col)`, with rows increasing downwards and each row
+//! alternating between triangles pointing up and triangles pointing down: the cell `(row, col)`
+//! points up exactly when `row + col` is even.  Each cell has a neighbour to its left and to its
+//! right in the same row, and a third neighbour across its horizontal side, which is below it if
+//! it points up and above it if it points down.
+
+use crate::cover::ExactCover;
+use num::BigUint;
+
+/// The three directions from a triangular cell to its neighbours
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TriangleDirection {
+    Left,
+    Right,
+
+    /// Across the horizontal side of the cell: down from a cell pointing up,
+    /// and up from a cell pointing down
+    Vertical,
+}
+
+/// Determines whether the cell at the given `(row, col)` position points up
+pub fn points_up((row, col): (isize, isize)) -> bool {
+    (row + col).rem_euclid(2) == 0
+}
+
+/// Returns the position next to the given `(row, col)` position in the given direction.
+/// Unlike on the square and hexagonal grids, this depends on which way the cell points.
+///
+/// # Examples
+///
+/// ```
+/// use dcc_tiler::triangle::{step, TriangleDirection};
+///
+/// assert_eq!(step((0, 0), TriangleDirection::Vertical), (1, 0));
+/// assert_eq!(step((0, 1), TriangleDirection::Vertical), (-1, 1));
+/// ```
+pub fn step((row, col): (isize, isize), direction: TriangleDirection) -> (isize, isize) {
+    match direction {
+        TriangleDirection::Left => (row, col - 1),
+        TriangleDirection::Right => (row, col + 1),
+        TriangleDirection::Vertical if points_up((row, col)) => (row + 1, col),
+        TriangleDirection::Vertical => (row - 1, col),
+    }
+}
+
+/// Translates a collection of cells so that the smallest row is zero, and the smallest column is
+/// zero or one (we can only translate by an even number of rows plus columns, since otherwise
+/// cells pointing up would point down), then sorts and deduplicates them
+fn normalize_cells(cells: &[(isize, isize)]) -> Vec<(isize, isize)> {
+    let min_row = cells.iter().map(|c| c.0).min().unwrap_or(0);
+    let mut min_col = cells.iter().map(|c| c.1).min().unwrap_or(0);
+
+    if (min_row + min_col).rem_euclid(2) != 0 {
+        min_col -= 1;
+    }
+
+    let mut cells: Vec<_> = cells
+        .iter()
+        .map(|(r, c)| (r - min_row, c - min_col))
+        .collect();
+    cells.sort_unstable();
+    cells.dedup();
+    cells
+}
+
+/// Returns the corners of a cell, as `(x, row)` points of the grid, where the x-coordinate is
+/// measured in half sides of a triangle.  The corners are the left and right ends of the
+/// horizontal side, and then the remaining corner, so that the sides between consecutive corners
+/// are the horizontal side, the right side and the left side, in that order.
+pub(crate) fn corners((row, col): (isize, isize)) -> [(isize, isize); 3] {
+    if points_up((row, col)) {
+        [(col + 1, row + 1), (col + 3, row + 1), (col + 2, row)]
+    } else {
+        [(col + 1, row), (col + 3, row), (col + 2, row + 1)]
+    }
+}
+
+/// Returns the cell with the given corners (in any order)
+fn from_corners(mut corners: [(isize, isize); 3]) -> (isize, isize) {
+    corners.sort_unstable_by_key(|(x, row)| (*row, *x));
+
+    if corners[0].1 == corners[1].1 {
+        // the horizontal side is at the top, so the cell points down
+        (corners[0].1, corners[0].0 - 1)
+    } else {
+        // the horizontal side is at the bottom, so the cell points up
+        (corners[0].1, corners[1].0 - 1)
+    }
+}
+
+/// A polyiamond, described by a walk through its cells (as for `Tile`) which starts at a
+/// cell pointing up
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TriangleTile {
+    pub directions: Vec<TriangleDirection>,
+}
+
+impl TriangleTile {
+    pub fn new(directions: Vec<TriangleDirection>) -> Self {
+        TriangleTile { directions }
+    }
+
+    /// Returns a tile consisting of a single triangle
+    pub fn single() -> Self {
+        TriangleTile::new(Vec::new())
+    }
+
+    /// Returns the rhombus made up of two triangles (a lozenge)
+    pub fn diamond() -> Self {
+        TriangleTile::new(vec![TriangleDirection::Vertical])
+    }
+
+    /// Returns the trapezoid made up of three triangles in a row
+    pub fn triamond() -> Self {
+        TriangleTile::new(vec![TriangleDirection::Right, TriangleDirection::Right])
+    }
+
+    /// Returns the hexagon made up of six triangles around a common corner
+    pub fn hexagon() -> Self {
+        use TriangleDirection::*;
+
+        TriangleTile::new(vec![Right, Right, Vertical, Left, Left])
+    }
+
+    /// Returns the `(row, col)` positions of the cells making up this tile, normalized as for
+    /// `Tile::cells` (except that the smallest column may be one, if the leftmost cell in the
+    /// top row points down).  The result is sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::triangle::TriangleTile;
+    ///
+    /// assert_eq!(TriangleTile::diamond().cells(), vec![(0, 0), (1, 0)]);
+    /// assert_eq!(TriangleTile::hexagon().cells().len(), 6);
+    /// ```
+    pub fn cells(&self) -> Vec<(isize, isize)> {
+        let mut current = (0, 0);
+        let mut cells = vec![current];
+
+        for direction in &self.directions {
+            current = step(current, *direction);
+            cells.push(current);
+        }
+
+        normalize_cells(&cells)
+    }
+
+    /// Returns the distinct orientations of this tile under the twelve rotations and
+    /// reflections of the triangular grid, each given by its cells (as for `cells`).
+    ///
+    /// Rotating by 60 degrees turns cells pointing up into cells pointing down, so unlike
+    /// `Tile::rotate` we can't just rotate the directions of the walk.  Instead we rotate the
+    /// corners of each cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::triangle::TriangleTile;
+    ///
+    /// assert_eq!(TriangleTile::single().orbit().len(), 2);
+    /// assert_eq!(TriangleTile::diamond().orbit().len(), 3);
+    /// assert_eq!(TriangleTile::triamond().orbit().len(), 6);
+    /// assert_eq!(TriangleTile::hexagon().orbit().len(), 1);
+    /// ```
+    pub fn orbit(&self) -> Vec<Vec<(isize, isize)>> {
+        // rotating by 60 degrees about the corner (0, 0), and reflecting in the vertical
+        // line through it, in the coordinates used by `corners`
+        let rotate = |(x, row): (isize, isize)| ((x - 3 * row) / 2, (x + row) / 2);
+        let reflect = |(x, row): (isize, isize)| (-x, row);
+
+        let mut orbit: Vec<Vec<(isize, isize)>> = Vec::new();
+        let mut cells = self.cells();
+
+        for _ in 0..2 {
+            for _ in 0..6 {
+                let normalized = normalize_cells(&cells);
+
+                if !orbit.contains(&normalized) {
+                    orbit.push(normalized);
+                }
+
+                cells = cells
+                    .iter()
+                    .map(|cell| from_corners(corners(*cell).map(rotate)))
+                    .collect();
+            }
+
+            cells = cells
+                .iter()
+                .map(|cell| from_corners(corners(*cell).map(reflect)))
+                .collect();
+        }
+
+        orbit
+    }
+}
+
+/// A region of the triangular grid, given by the cells of a `width` x `height` rectangle of
+/// positions which aren't marked
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TriangleBoard {
+    pub width: usize,
+    pub height: usize,
+    pub board: Vec<Vec<bool>>,
+}
+
+impl TriangleBoard {
+    /// Returns a triangle (pointing up) with `side` triangles along each side
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::triangle::TriangleBoard;
+    ///
+    /// assert_eq!(TriangleBoard::triangle(3).positions().len(), 9);
+    /// ```
+    pub fn triangle(side: usize) -> Self {
+        TriangleBoard::trapezoid(0, side)
+    }
+
+    /// Returns a trapezoid with `top` triangles along its top side and `bottom` triangles along
+    /// its bottom side, which is the triangle of side `bottom` with the top `top` rows removed
+    ///
+    /// # Panics
+    ///
+    /// Will panic unless top < bottom
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::triangle::{TriangleBoard, TriangleTile};
+    ///
+    /// let board = TriangleBoard::trapezoid(2, 3);
+    /// assert_eq!(board.positions().len(), 5);
+    /// assert_eq!(board.count_tilings(&[TriangleTile::triamond()]), 0u32.into());
+    /// ```
+    pub fn trapezoid(top: usize, bottom: usize) -> Self {
+        assert!(top < bottom);
+
+        // the column of the apex of the triangle, chosen so that the
+        // leftmost cell of each row points up
+        let apex = bottom - 1 + (bottom - 1 + top) % 2;
+
+        TriangleBoard::from_marks(
+            (top..bottom)
+                .map(|row| {
+                    (0..apex + bottom)
+                        .map(|col| col + row < apex || col > apex + row)
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns a hexagon with `side` triangles along each side
+    ///
+    /// # Panics
+    ///
+    /// Will panic if side = 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::triangle::{TriangleBoard, TriangleTile};
+    ///
+    /// let board = TriangleBoard::hexagon(2);
+    /// assert_eq!(board.positions().len(), 24);
+    ///
+    /// // MacMahon's formula for the number of lozenge tilings of a hexagon
+    /// assert_eq!(board.count_tilings(&[TriangleTile::diamond()]), 20u32.into());
+    /// ```
+    pub fn hexagon(side: usize) -> Self {
+        assert!(side > 0);
+
+        // the middle column, chosen so that the leftmost cell of the top row points up
+        let middle = 2 * side - 1 + (side + 1) % 2;
+
+        TriangleBoard::from_marks(
+            (0..2 * side)
+                .map(|row| {
+                    // the number of cells on either side of the middle column
+                    let spread = side + row.min(2 * side - 1 - row);
+
+                    (0..middle + 2 * side)
+                        .map(|col| col + spread < middle || col > middle + spread)
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Creates a board from the marked positions of a rectangle, indexed by `(row, col)`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the board is empty.
+    pub fn from_marks(board: Vec<Vec<bool>>) -> Self {
+        TriangleBoard {
+            width: board[0].len(),
+            height: board.len(),
+            board,
+        }
+    }
+
+    /// Returns the `(row, col)` positions which aren't marked, in row-major order
+    pub fn positions(&self) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+            .filter(|(row, col)| !self.board[*row][*col])
+            .collect()
+    }
+
+    /// Returns the given `(row, col)` position if it's an unmarked position of the board
+    fn position(&self, (row, col): (isize, isize)) -> Option<(usize, usize)> {
+        if row < 0 || col < 0 || row as usize >= self.height || col as usize >= self.width {
+            return None;
+        }
+
+        let position = (row as usize, col as usize);
+        Some(position).filter(|(row, col)| !self.board[*row][*col])
+    }
+
+    /// Returns the position next to the given `(row, col)` position in the given direction,
+    /// or None if that isn't an unmarked position of the board
+    pub fn neighbour(
+        &self,
+        (row, col): (usize, usize),
+        direction: TriangleDirection,
+    ) -> Option<(usize, usize)> {
+        self.position(step((row as isize, col as isize), direction))
+    }
+
+    /// Returns every way of placing down one of the given tiles (in any orientation) on the
+    /// unmarked positions of the board, along with the index of its shape in `shapes`
+    pub fn placements(&self, shapes: &[TriangleTile]) -> Vec<(usize, Vec<(usize, usize)>)> {
+        let mut placements = Vec::new();
+
+        for (shape, tile) in shapes.iter().enumerate() {
+            for cells in tile.orbit() {
+                let first = cells[0];
+
+                // line up the first cell of the tile with each position pointing the same way
+                for (row, col) in self.positions() {
+                    let (row, col) = (row as isize, col as isize);
+
+                    if points_up((row, col)) != points_up(first) {
+                        continue;
+                    }
+
+                    let placement: Option<Vec<(usize, usize)>> = cells
+                        .iter()
+                        .map(|(r, c)| self.position((row + r - first.0, col + c - first.1)))
+                        .collect();
+
+                    if let Some(placement) = placement {
+                        placements.push((shape, placement));
+                    }
+                }
+            }
+        }
+
+        placements
+    }
+
+    /// Returns the exact cover problem of tiling this board by the given tiles,
+    /// along with the placements making it up
+    fn exact_cover(&self, shapes: &[TriangleTile]) -> (ExactCover, Vec<Vec<(usize, usize)>>) {
+        let positions = self.positions();
+        let placements: Vec<_> = self
+            .placements(shapes)
+            .into_iter()
+            .map(|(_, placement)| placement)
+            .collect();
+
+        (
+            ExactCover::from_positions(&positions, &placements),
+            placements,
+        )
+    }
+
+    /// Counts the tilings of this board by the given tiles, which may be rotated and reflected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dcc_tiler::triangle::{TriangleBoard, TriangleTile};
+    ///
+    /// let board = TriangleBoard::triangle(2);
+    /// assert_eq!(board.count_tilings(&[TriangleTile::single()]), 1u32.into());
+    /// assert_eq!(board.count_tilings(&[TriangleTile::diamond()]), 0u32.into());
+    /// ```
+    pub fn count_tilings(&self, shapes: &[TriangleTile]) -> BigUint {
+        self.exact_cover(shapes).0.count()
+    }
+
+    /// Finds a tiling of this board by the given tiles, returned as the `(row, col)` positions
+    /// covered by each tile, or returns None if the board can't be tiled
+    pub fn find_tiling(&self, shapes: &[TriangleTile]) -> Option<Vec<Vec<(usize, usize)>>> {
+        let (problem, placements) = self.exact_cover(shapes);
+
+        problem.find().map(|solution| {
+            solution
+                .into_iter()
+                .map(|i| placements[i].clone())
+                .collect()
+        })
+    }
+}